@@ -0,0 +1,119 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, RwLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::types::{Budget, BudgetKind, BudgetPolicy, BudgetViolation, GuardKind, Location};
+
+// The budgets applicable to all the guards of the locks created at the given
+// locations, and the ones applicable to the guards acquired at given sites.
+static LOCK_BUDGETS: LazyLock<RwLock<HashMap<Location, Budget>>> = LazyLock::new(Default::default);
+static SITE_BUDGETS: LazyLock<RwLock<HashMap<Location, Budget>>> = LazyLock::new(Default::default);
+
+// Allows the budgets to be skipped without locking until one is set.
+static HAS_BUDGETS: AtomicBool = AtomicBool::new(false);
+
+/// Sets or removes the budget applicable to the guards acquired at the given
+/// site, whose limits take precedence over the ones of their lock (see
+/// `LockOptions::budget`). The sites are the `guard_location`s of the guards.
+pub fn set_site_budget(site: Location, budget: Option<Budget>) {
+    set_budget(&SITE_BUDGETS, site, budget);
+}
+
+/// Sets or removes the budget applicable to all the guards of the lock
+/// created at the given location.
+pub(crate) fn set_lock_budget(lock_location: Location, budget: Option<Budget>) {
+    set_budget(&LOCK_BUDGETS, lock_location, budget);
+}
+
+fn set_budget(
+    budgets: &RwLock<HashMap<Location, Budget>>,
+    location: Location,
+    budget: Option<Budget>,
+) {
+    let mut budgets = budgets.write().unwrap();
+    match budget {
+        Some(budget) => {
+            budgets.insert(location, budget);
+            HAS_BUDGETS.store(true, Ordering::Relaxed);
+        }
+        None => {
+            budgets.remove(&location);
+        }
+    }
+}
+
+/// Checks the given wait or hold time against the applicable budget, and
+/// returns the violation, if any, along with the policy to enforce.
+pub(crate) fn check(
+    kind: BudgetKind,
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    duration: Duration,
+) -> Option<(BudgetViolation, BudgetPolicy)> {
+    if !HAS_BUDGETS.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let select = |budget: &Budget| {
+        let limit = match kind {
+            BudgetKind::Wait => budget.max_wait,
+            BudgetKind::Hold => budget.max_hold,
+        };
+        Some((limit?, budget.policy.clone()))
+    };
+    // The limits missing from the budget of the site are taken from the lock.
+    let site_limit = SITE_BUDGETS
+        .read()
+        .unwrap()
+        .get(guard_location)
+        .and_then(select);
+    let (limit, policy) = site_limit.or_else(|| {
+        LOCK_BUDGETS
+            .read()
+            .unwrap()
+            .get(lock_location)
+            .and_then(select)
+    })?;
+
+    if duration <= limit {
+        return None;
+    }
+
+    let violation = BudgetViolation {
+        kind,
+        lock_location: lock_location.clone(),
+        guard_location: guard_location.clone(),
+        guard_kind,
+        guard_index,
+        duration,
+        limit,
+    };
+
+    Some((violation, policy))
+}
+
+/// Enforces the given policy; this must not be called while the lock
+/// registry is locked, as the callbacks and panics could otherwise affect it.
+pub(crate) fn enforce(violation: BudgetViolation, policy: BudgetPolicy) {
+    match policy {
+        BudgetPolicy::Callback(callback) => callback(&violation),
+        BudgetPolicy::Count => {}
+        // A panic while already panicking would abort the process.
+        BudgetPolicy::Panic if cfg!(debug_assertions) && !thread::panicking() => {
+            panic!("locktick: {violation}");
+        }
+        BudgetPolicy::Log | BudgetPolicy::Panic => {
+            let message = format!("locktick: {violation}\n");
+            let _ = io::stderr().lock().write_all(message.as_bytes());
+        }
+    }
+}
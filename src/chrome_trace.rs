@@ -0,0 +1,177 @@
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    export::json_string,
+    types::{GuardKind, Location},
+};
+
+// Indicates whether the events are currently being recorded.
+static RECORDING: AtomicBool = AtomicBool::new(false);
+
+// Contains the current (or the most recent) recording.
+static RECORDING_DATA: Mutex<Option<Recording>> = Mutex::new(None);
+
+// Provides a common source of identifiers for all the threads.
+static THREAD_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// The maximum number of events retained in a single recording; once it is
+/// reached, further events are discarded until the recording is restarted.
+pub const MAX_EVENTS: usize = 1_000_000;
+
+thread_local! {
+    // The identifier of the current thread; 0 means that it hasn't been assigned yet.
+    static THREAD_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Starts recording the wait and hold events for all the guards, discarding
+/// any events that might have been recorded previously; at most `MAX_EVENTS`
+/// events are retained.
+pub fn start_recording() {
+    *RECORDING_DATA.lock().unwrap() = Some(Recording {
+        epoch: Instant::now(),
+        events: Vec::new(),
+        thread_names: HashMap::new(),
+    });
+    RECORDING.store(true, Ordering::Release);
+}
+
+/// Stops recording the wait and hold events; the ones that were already
+/// recorded are retained until the recording is started again.
+pub fn stop_recording() {
+    RECORDING.store(false, Ordering::Release);
+}
+
+/// Writes all the recorded events to the given writer in the Chrome Trace
+/// Event format, which can be loaded in Perfetto or `chrome://tracing`.
+pub fn write_chrome_trace<W: Write>(mut writer: W) -> io::Result<()> {
+    let recording = RECORDING_DATA.lock().unwrap();
+    let Some(recording) = recording.as_ref() else {
+        return writer.write_all(b"[]");
+    };
+
+    let pid = std::process::id();
+    let mut entries = Vec::with_capacity(recording.thread_names.len() + recording.events.len());
+    for (tid, thread_name) in &recording.thread_names {
+        entries.push(format!(
+            r#"{{"name":"thread_name","ph":"M","pid":{pid},"tid":{tid},"args":{{"name":{}}}}}"#,
+            json_string(thread_name),
+        ));
+    }
+    for event in &recording.events {
+        let (cat, name) = match event.kind {
+            EventKind::Wait => ("wait", "wait"),
+            EventKind::CancelledWait => ("wait", "wait (cancelled)"),
+            EventKind::Hold => ("hold", "hold"),
+        };
+        let ts = event.start.saturating_duration_since(recording.epoch);
+        entries.push(format!(
+            r#"{{"name":{},"cat":"{cat}","ph":"X","ts":{},"dur":{},"pid":{pid},"tid":{},"args":{{"lock":{},"guard":{},"kind":"{:?}","index":{}}}}}"#,
+            json_string(&format!("{name} {}", event.lock_location)),
+            micros(ts),
+            micros(event.duration),
+            event.tid,
+            json_string(&event.lock_location.to_string()),
+            json_string(&event.guard_location.to_string()),
+            event.guard_kind,
+            event.guard_index,
+        ));
+    }
+
+    writer.write_all(b"[")?;
+    for (i, entry) in entries.iter().enumerate() {
+        if i != 0 {
+            writer.write_all(b",\n")?;
+        }
+        writer.write_all(entry.as_bytes())?;
+    }
+    writer.write_all(b"]")
+}
+
+/// The type of a recorded event.
+#[derive(Clone, Copy)]
+pub(crate) enum EventKind {
+    Wait,
+    CancelledWait,
+    Hold,
+}
+
+// The data collected while recording.
+struct Recording {
+    epoch: Instant,
+    events: Vec<Event>,
+    thread_names: HashMap<u64, String>,
+}
+
+// A single recorded wait or hold event.
+struct Event {
+    kind: EventKind,
+    lock_location: Location,
+    guard_location: Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    start: Instant,
+    duration: Duration,
+    tid: u64,
+}
+
+/// Records an event if the recording is currently active.
+pub(crate) fn record(
+    kind: EventKind,
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    start: Instant,
+    duration: Duration,
+) {
+    if !RECORDING.load(Ordering::Acquire) {
+        return;
+    }
+
+    let tid = THREAD_ID.with(|id| {
+        if id.get() == 0 {
+            id.set(THREAD_COUNTER.fetch_add(1, Ordering::Relaxed));
+        }
+        id.get()
+    });
+
+    if let Some(recording) = RECORDING_DATA
+        .lock()
+        .unwrap()
+        .as_mut()
+        .filter(|recording| recording.events.len() < MAX_EVENTS)
+    {
+        recording.thread_names.entry(tid).or_insert_with(|| {
+            let thread = thread::current();
+            thread
+                .name()
+                .map(|name| name.to_owned())
+                .unwrap_or_else(|| format!("{:?}", thread.id()))
+        });
+        recording.events.push(Event {
+            kind,
+            lock_location: lock_location.clone(),
+            guard_location: guard_location.clone(),
+            guard_kind,
+            guard_index,
+            start,
+            duration,
+            tid,
+        });
+    }
+}
+
+// Converts the duration to microseconds, the unit used by the Chrome Trace Event format.
+fn micros(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1_000.0
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{LazyLock, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    lock_info::{call_location, is_enabled},
+    types::Location,
+};
+
+// Contains data on all created condition variables.
+static CONDVAR_INFOS: LazyLock<RwLock<HashMap<Location, Mutex<CondvarInfo>>>> =
+    LazyLock::new(Default::default);
+
+/// Returns a vector containing snapshots of the data related to all the
+/// condition variables.
+pub fn condvar_snapshots() -> Vec<CondvarInfo> {
+    CONDVAR_INFOS
+        .read()
+        .unwrap()
+        .values()
+        .map(|info| info.lock().unwrap().clone())
+        .collect()
+}
+
+#[cfg(feature = "test")]
+pub(crate) fn clear_condvar_infos() {
+    CONDVAR_INFOS.write().unwrap().clear();
+}
+
+/// Contains all the details related to a given condition variable, and it
+/// can only be obtained through a call to `condvar_snapshots`. While a task
+/// waits on a condition variable, the guard it waited with is released, and
+/// waking up involves reacquiring it, which is accounted for as a new use of
+/// the guard; its wait time is measured from the latest notification.
+#[derive(Debug, Clone)]
+pub struct CondvarInfo {
+    pub location: Location,
+    /// The number of calls to `notify_one`.
+    pub num_notify_one: usize,
+    /// The number of calls to `notify_all`.
+    pub num_notify_all: usize,
+    /// The number of waits that ended without timing out, including the
+    /// spurious wakeups.
+    pub num_wakeups: usize,
+    /// The number of waits that timed out.
+    pub num_timeouts: usize,
+    num_waiting: usize,
+    last_notification: Option<Instant>,
+}
+
+impl CondvarInfo {
+    /// Registers the creation of a condition variable, and returns its location.
+    pub(crate) fn register() -> Location {
+        let location = call_location();
+        CONDVAR_INFOS
+            .write()
+            .unwrap()
+            .entry(location.clone())
+            .or_insert_with(|| {
+                Mutex::new(Self {
+                    location: location.clone(),
+                    num_notify_one: 0,
+                    num_notify_all: 0,
+                    num_wakeups: 0,
+                    num_timeouts: 0,
+                    num_waiting: 0,
+                    last_notification: None,
+                })
+            });
+
+        location
+    }
+
+    /// Returns the number of tasks currently waiting on the condition variable.
+    pub fn num_waiting(&self) -> usize {
+        self.num_waiting
+    }
+
+    /// Registers a call to `notify_one` or `notify_all`.
+    pub(crate) fn notified(location: &Location, all: bool) {
+        if !is_enabled() {
+            return;
+        }
+
+        update(location, |info| {
+            if all {
+                info.num_notify_all += 1;
+            } else {
+                info.num_notify_one += 1;
+            }
+            info.last_notification = Some(Instant::now());
+        });
+    }
+
+    /// Registers the start of a wait, and returns its timestamp, unless the
+    /// accounting is disabled.
+    pub(crate) fn wait_started(location: &Location) -> Option<Instant> {
+        if !is_enabled() {
+            return None;
+        }
+
+        update(location, |info| info.num_waiting += 1);
+        Some(Instant::now())
+    }
+
+    /// Registers the end of a wait that started at the given time, and that
+    /// timed out if `deadline` is provided. Returns the estimated time spent
+    /// reacquiring the lock, i.e. since the notification or the deadline.
+    pub(crate) fn wait_finished(
+        location: &Location,
+        started: Instant,
+        deadline: Option<Instant>,
+    ) -> Duration {
+        let mut woken_up = deadline;
+        update(location, |info| {
+            info.num_waiting = info.num_waiting.saturating_sub(1);
+            if deadline.is_some() {
+                info.num_timeouts += 1;
+            } else {
+                info.num_wakeups += 1;
+                // The spurious wakeups aren't preceded by notifications.
+                woken_up = info.last_notification.filter(|time| *time >= started);
+            }
+        });
+
+        woken_up.map_or(Duration::ZERO, |time| time.elapsed())
+    }
+}
+
+// Applies the given update to the data related to a condition variable.
+fn update<F: FnOnce(&mut CondvarInfo)>(location: &Location, f: F) {
+    if let Some(info) = CONDVAR_INFOS.read().unwrap().get(location) {
+        f(&mut info.lock().unwrap());
+    }
+}
+
+impl fmt::Display for CondvarInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (Condvar): waiting: {}; notify_one: {}; notify_all: {}; wakeups: {}; timeouts: {}",
+            self.location,
+            self.num_waiting,
+            self.num_notify_one,
+            self.num_notify_all,
+            self.num_wakeups,
+            self.num_timeouts,
+        )
+    }
+}
@@ -0,0 +1,459 @@
+use std::{
+    env::{self, VarError},
+    error::Error,
+    fmt,
+    io::{self, Write},
+    path::PathBuf,
+    sync::Once,
+    time::Duration,
+};
+
+#[cfg(feature = "tracing")]
+use tracing::Level;
+
+#[cfg(feature = "tracing")]
+use crate::tracing::{set_thresholds, thresholds, Thresholds};
+use crate::{
+    filter::{filters, set_filters, Filters},
+    lock_info::{is_enabled, set_enabled},
+    sampling::{sampling, set_sampling, Sampling},
+    types::LockKind,
+};
+
+/// The configuration of `locktick`; it can be applied programmatically, or
+/// read from the `LOCKTICK_*` environment variables, which happens upon the
+/// creation of the first lock.
+///
+/// The following environment variables are recognized:
+/// - `LOCKTICK_ENABLED`: `true` or `false` (or `1` and `0`)
+/// - `LOCKTICK_SAMPLING`: `all`, a number N (1 in N acquisitions is tracked), or an
+///   interval, e.g. `10ms` (at most one acquisition per interval is tracked)
+/// - `LOCKTICK_INCLUDE`, `LOCKTICK_EXCLUDE`: comma-separated lists of path prefixes;
+///   entries prefixed with `module:`, `name:` or `kind:` (`mutex`, `rwlock`,
+///   `semaphore` or `notify`) match the modules, names or kinds of the locks instead
+/// - `LOCKTICK_WAIT_THRESHOLD`, `LOCKTICK_HOLD_THRESHOLD`: durations, e.g. `5ms`
+/// - `LOCKTICK_THRESHOLD_LEVEL`: `error`, `warn`, `info`, `debug` or `trace`; these
+///   3 require the `tracing` feature
+/// - `LOCKTICK_HTTP`: the address to serve the lock data at, e.g. `127.0.0.1:9000`
+/// - `LOCKTICK_UDS`: the path of the Unix domain socket to accept commands at
+/// - `LOCKTICK_REPORT_ON_EXIT`: `true` or `false` (or `1` and `0`); requires the
+///   `report` feature
+///
+/// The captures of stacks and events are only available programmatically, as
+/// their results need to be written out by the program.
+///
+/// Some of the settings are only available with the related features, so it
+/// can't be constructed directly; start with `Config::default()` or
+/// `Config::current()` and set the fields instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Config {
+    /// Whether the accounting is enabled (see `set_enabled`).
+    pub enabled: bool,
+    /// The sampling mode (see `set_sampling`).
+    pub sampling: Sampling,
+    /// Whether to start capturing the call stacks of guard users; requires
+    /// the `stacks` feature.
+    pub capture_stacks: bool,
+    /// Whether to start recording the wait and hold events; requires the
+    /// `chrome_trace` feature.
+    pub record_chrome_trace: bool,
+    /// The filters determining which locks are tracked (see `set_filters`).
+    pub filters: Filters,
+    /// The thresholds for the waits and holds resulting in events being
+    /// emitted (see `tracing::set_thresholds`).
+    #[cfg(feature = "tracing")]
+    pub thresholds: Option<Thresholds>,
+    /// The address to start serving the lock data at; requires the `http` feature.
+    pub http_addr: Option<String>,
+    /// The path of the Unix domain socket to start accepting commands at;
+    /// requires the `uds` feature.
+    pub uds_path: Option<PathBuf>,
+    /// Whether to print a summary of the lock data when the process exits
+    /// (see `report_on_exit`).
+    #[cfg(feature = "report")]
+    pub report_on_exit: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sampling: Sampling::All,
+            capture_stacks: false,
+            record_chrome_trace: false,
+            filters: Default::default(),
+            #[cfg(feature = "tracing")]
+            thresholds: None,
+            http_addr: None,
+            uds_path: None,
+            #[cfg(feature = "report")]
+            report_on_exit: false,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the currently applicable settings; the ones that only start
+    /// something (e.g. `capture_stacks` or `http_addr`) are left unset.
+    pub fn current() -> Self {
+        Self {
+            enabled: is_enabled(),
+            sampling: sampling(),
+            filters: filters(),
+            #[cfg(feature = "tracing")]
+            thresholds: thresholds(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the current configuration (see `Config::current`), with the
+    /// settings specified via the `LOCKTICK_*` environment variables applied.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::current().with_env()
+    }
+
+    // Returns the configuration with the settings specified via the `LOCKTICK_*`
+    // environment variables taking precedence over its own.
+    fn with_env(self) -> Result<Self, ConfigError> {
+        let mut config = self;
+
+        if let Some(enabled) = parse_var("LOCKTICK_ENABLED", parse_bool)? {
+            config.enabled = enabled;
+        }
+        if let Some(sampling) = parse_var("LOCKTICK_SAMPLING", parse_sampling)? {
+            config.sampling = sampling;
+        }
+        if let Some(include) = parse_var("LOCKTICK_INCLUDE", parse_filter)? {
+            let filters = &mut config.filters;
+            filters.include_paths = include.paths;
+            filters.include_modules = include.modules;
+            filters.include_names = include.names;
+            filters.include_kinds = include.kinds;
+        }
+        if let Some(exclude) = parse_var("LOCKTICK_EXCLUDE", parse_filter)? {
+            let filters = &mut config.filters;
+            filters.exclude_paths = exclude.paths;
+            filters.exclude_modules = exclude.modules;
+            filters.exclude_names = exclude.names;
+            filters.exclude_kinds = exclude.kinds;
+        }
+
+        let wait_threshold = parse_var("LOCKTICK_WAIT_THRESHOLD", parse_duration)?;
+        let hold_threshold = parse_var("LOCKTICK_HOLD_THRESHOLD", parse_duration)?;
+        #[cfg(feature = "tracing")]
+        {
+            let level = parse_var("LOCKTICK_THRESHOLD_LEVEL", |value| {
+                value
+                    .parse::<Level>()
+                    .map_err(|_| "expected `error`, `warn`, `info`, `debug` or `trace`".into())
+            })?;
+            if wait_threshold.is_some() || hold_threshold.is_some() || level.is_some() {
+                let mut thresholds = config.thresholds.unwrap_or_default();
+                thresholds.wait = wait_threshold.or(thresholds.wait);
+                thresholds.hold = hold_threshold.or(thresholds.hold);
+                thresholds.level = level.unwrap_or(thresholds.level);
+                config.thresholds = Some(thresholds);
+            }
+        }
+        #[cfg(not(feature = "tracing"))]
+        if wait_threshold.is_some()
+            || hold_threshold.is_some()
+            || parse_var("LOCKTICK_THRESHOLD_LEVEL", |_| Ok(()))?.is_some()
+        {
+            return Err(ConfigError::MissingFeature {
+                setting: "thresholds",
+                feature: "tracing",
+            });
+        }
+
+        if let Some(http_addr) = parse_var("LOCKTICK_HTTP", |value| Ok(value.to_owned()))? {
+            config.http_addr = Some(http_addr);
+        }
+        if let Some(uds_path) = parse_var("LOCKTICK_UDS", |value| Ok(value.into()))? {
+            config.uds_path = Some(uds_path);
+        }
+        let report_on_exit = parse_var("LOCKTICK_REPORT_ON_EXIT", parse_bool)?;
+        #[cfg(feature = "report")]
+        if let Some(report_on_exit) = report_on_exit {
+            config.report_on_exit = report_on_exit;
+        }
+        #[cfg(not(feature = "report"))]
+        if report_on_exit == Some(true) {
+            return Err(ConfigError::MissingFeature {
+                setting: "report_on_exit",
+                feature: "report",
+            });
+        }
+
+        Ok(config)
+    }
+
+    /// Applies the configuration, with the settings specified via the
+    /// `LOCKTICK_*` environment variables taking precedence over its own. Any
+    /// settings that require features that aren't enabled, invalid environment
+    /// variables, or servers that can't be bound to their addresses result in
+    /// an error, in which case nothing is applied.
+    pub fn apply(&self) -> Result<(), ConfigError> {
+        init_from_env();
+
+        let mut config = self.clone().with_env()?;
+        // The servers requested via the environment variables have already
+        // been started along with the rest of the environment configuration.
+        if env::var_os("LOCKTICK_HTTP").is_some() {
+            config.http_addr = None;
+        }
+        if env::var_os("LOCKTICK_UDS").is_some() {
+            config.uds_path = None;
+        }
+
+        config.apply_settings()
+    }
+
+    // Checks if all the settings are supported.
+    fn validate(&self) -> Result<(), ConfigError> {
+        let required_features = [
+            (
+                self.capture_stacks,
+                cfg!(feature = "stacks"),
+                "capture_stacks",
+                "stacks",
+            ),
+            (
+                self.record_chrome_trace,
+                cfg!(feature = "chrome_trace"),
+                "record_chrome_trace",
+                "chrome_trace",
+            ),
+            (
+                self.http_addr.is_some(),
+                cfg!(feature = "http"),
+                "http_addr",
+                "http",
+            ),
+            (
+                self.uds_path.is_some(),
+                cfg!(all(unix, feature = "uds")),
+                "uds_path",
+                "uds",
+            ),
+        ];
+
+        for (is_set, is_supported, setting, feature) in required_features {
+            if is_set && !is_supported {
+                return Err(ConfigError::MissingFeature { setting, feature });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_settings(&self) -> Result<(), ConfigError> {
+        self.validate()?;
+
+        // Bind the servers first, so that nothing is applied if it fails.
+        #[cfg(feature = "http")]
+        let http_listener = self
+            .http_addr
+            .as_ref()
+            .map(|addr| std::net::TcpListener::bind(addr.as_str()))
+            .transpose()
+            .map_err(ConfigError::Io)?;
+        #[cfg(all(unix, feature = "uds"))]
+        let uds_listener = self
+            .uds_path
+            .as_ref()
+            .map(std::os::unix::net::UnixListener::bind)
+            .transpose()
+            .map_err(ConfigError::Io)?;
+
+        set_enabled(self.enabled);
+        set_sampling(self.sampling);
+        set_filters(self.filters.clone());
+        #[cfg(feature = "tracing")]
+        set_thresholds(self.thresholds.clone());
+        #[cfg(feature = "stacks")]
+        if self.capture_stacks {
+            crate::stacks::start_capture();
+        }
+        #[cfg(feature = "chrome_trace")]
+        if self.record_chrome_trace {
+            crate::chrome_trace::start_recording();
+        }
+        #[cfg(feature = "http")]
+        if let Some(listener) = http_listener {
+            crate::http::serve(listener).map_err(ConfigError::Io)?;
+        }
+        #[cfg(all(unix, feature = "uds"))]
+        if let Some(listener) = uds_listener {
+            crate::uds::serve(listener).map_err(ConfigError::Io)?;
+        }
+        #[cfg(feature = "report")]
+        if self.report_on_exit {
+            crate::report::report_on_exit();
+        }
+
+        Ok(())
+    }
+}
+
+/// An error related to the configuration.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// An environment variable has an invalid value.
+    InvalidValue {
+        var: &'static str,
+        value: String,
+        reason: String,
+    },
+    /// A setting requires a feature that isn't enabled.
+    MissingFeature {
+        setting: &'static str,
+        feature: &'static str,
+    },
+    /// A server couldn't be started.
+    Io(io::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidValue { var, value, reason } => {
+                write!(f, "invalid value of {var} ({value:?}): {reason}")
+            }
+            Self::MissingFeature { setting, feature } => {
+                write!(f, "{setting} requires the `{feature}` feature")
+            }
+            Self::Io(e) => write!(f, "couldn't start a server: {e}"),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Applies the configuration read from the environment variables, unless it
+/// has already happened; any errors are printed to the standard error output.
+pub(crate) fn init_from_env() {
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        if let Err(e) = Config::from_env().and_then(|config| config.apply_settings()) {
+            let message = format!("locktick: the environment configuration wasn't applied: {e}\n");
+            let _ = io::stderr().lock().write_all(message.as_bytes());
+        }
+    });
+}
+
+// Reads and parses the given environment variable, if it is set.
+fn parse_var<T, F: Fn(&str) -> Result<T, String>>(
+    var: &'static str,
+    parse: F,
+) -> Result<Option<T>, ConfigError> {
+    let value = match env::var(var) {
+        Ok(value) => value,
+        Err(VarError::NotPresent) => return Ok(None),
+        Err(VarError::NotUnicode(value)) => {
+            return Err(ConfigError::InvalidValue {
+                var,
+                value: value.to_string_lossy().into_owned(),
+                reason: "not valid unicode".into(),
+            })
+        }
+    };
+
+    parse(value.trim())
+        .map(Some)
+        .map_err(|reason| ConfigError::InvalidValue { var, value, reason })
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "1" | "true" => Ok(true),
+        "0" | "false" => Ok(false),
+        _ => Err("expected `true`, `false`, `1` or `0`".into()),
+    }
+}
+
+fn parse_list(value: &str) -> Result<Vec<String>, String> {
+    Ok(value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+// The criteria of either the inclusion or the exclusion filters.
+#[derive(Default)]
+struct FilterCriteria {
+    paths: Vec<String>,
+    modules: Vec<String>,
+    names: Vec<String>,
+    kinds: Vec<LockKind>,
+}
+
+fn parse_filter(value: &str) -> Result<FilterCriteria, String> {
+    let mut criteria = FilterCriteria::default();
+    for item in parse_list(value)? {
+        if let Some(module) = item.strip_prefix("module:") {
+            criteria.modules.push(module.trim().into());
+        } else if let Some(name) = item.strip_prefix("name:") {
+            criteria.names.push(name.trim().into());
+        } else if let Some(kind) = item.strip_prefix("kind:") {
+            let kind = match kind.trim() {
+                "mutex" => LockKind::Mutex,
+                "rwlock" => LockKind::RwLock,
+                "semaphore" => LockKind::Semaphore,
+                "notify" => LockKind::Notify,
+                _ => {
+                    return Err(
+                        "expected a kind of `mutex`, `rwlock`, `semaphore` or `notify`".into(),
+                    )
+                }
+            };
+            criteria.kinds.push(kind);
+        } else {
+            criteria.paths.push(item);
+        }
+    }
+
+    Ok(criteria)
+}
+
+fn parse_sampling(value: &str) -> Result<Sampling, String> {
+    if value == "all" {
+        return Ok(Sampling::All);
+    }
+    if let Ok(n) = value.parse::<u64>() {
+        return Ok(Sampling::OneIn(n));
+    }
+    parse_duration(value)
+        .map(Sampling::Interval)
+        .map_err(|_| "expected `all`, a number, or a duration, e.g. `10ms`".into())
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| "expected a duration, e.g. `10ms`".to_owned())?;
+    let unit_secs = match unit.trim() {
+        "ns" => 1e-9,
+        "us" => 1e-6,
+        "ms" => 1e-3,
+        "s" => 1.0,
+        _ => return Err("expected a unit of `ns`, `us`, `ms` or `s`".into()),
+    };
+
+    Duration::try_from_secs_f64(number * unit_secs).map_err(|e| e.to_string())
+}
@@ -0,0 +1,316 @@
+use std::{cmp::Reverse, fmt::Write, thread::ThreadId, time::Duration};
+
+use crate::lock_info::{GuardInfo, LockInfo};
+
+/// Returns a human-readable description of the given locks and their guards.
+pub fn to_text(locks: &[LockInfo]) -> String {
+    let mut ret = String::new();
+    for lock in sorted(locks) {
+        let _ = writeln!(ret, "{}", lock.heading());
+        for guard in sorted_guards(lock) {
+            let _ = writeln!(ret, "- {guard}");
+        }
+    }
+    ret
+}
+
+/// Returns a human-readable description of the given locks and their guards,
+/// including the current holders and waiters of each guard.
+pub fn to_detailed_text(locks: &[LockInfo]) -> String {
+    detailed_text(locks, None)
+}
+
+/// Returns the same description as `to_detailed_text`, marking the holders
+/// and waiters belonging to the given thread.
+pub(crate) fn detailed_text(locks: &[LockInfo], thread: Option<ThreadId>) -> String {
+    let mut ret = String::new();
+    for lock in sorted(locks) {
+        let _ = writeln!(ret, "{}", lock.heading());
+        for guard in sorted_guards(lock) {
+            let _ = writeln!(ret, "- {guard}");
+            let (held, awaited) = thread
+                .map(|thread| (guard.held_by(thread), guard.awaited_by(thread)))
+                .unwrap_or_default();
+            for (idx, duration) in guard.active_durations() {
+                let mark = if held.contains(&idx) {
+                    " (this thread)"
+                } else {
+                    ""
+                };
+                let _ = writeln!(ret, "  - #{idx} held for {duration:?}{mark}");
+            }
+            for (idx, duration) in guard.waiting_durations() {
+                let mark = if awaited.contains(&idx) {
+                    " (this thread)"
+                } else {
+                    ""
+                };
+                let _ = writeln!(ret, "  - #{idx} waiting for {duration:?}{mark}");
+            }
+        }
+    }
+    ret
+}
+
+/// Returns a JSON array describing the given locks and their guards.
+pub fn to_json(locks: &[LockInfo]) -> String {
+    let mut ret = String::from("[");
+    for (i, lock) in sorted(locks).into_iter().enumerate() {
+        if i != 0 {
+            ret.push(',');
+        }
+        let _ = write!(
+            ret,
+            r#"{{"location":{},"kind":"{:?}","name":{},"permits":{},"notifications":{},"guards":["#,
+            json_string(&lock.location.to_string()),
+            lock.kind,
+            lock.name.as_deref().map_or("null".into(), json_string),
+            lock.permits.map_or("null".into(), |permits| format!(
+                r#"{{"held":{},"max_held":{},"available":{}}}"#,
+                permits.held, permits.max_held, permits.available
+            )),
+            lock.notifications
+                .map_or("null".into(), |notifications| format!(
+                    r#"{{"notify_one":{},"notify_waiters":{}}}"#,
+                    notifications.notify_one, notifications.notify_waiters
+                )),
+        );
+        for (j, guard) in sorted_guards(lock).into_iter().enumerate() {
+            if j != 0 {
+                ret.push(',');
+            }
+            let _ = write!(
+                ret,
+                r#"{{"location":{},"kind":"{:?}","num_uses":{},"estimated_uses":{},"sampled":{},"active_uses":{},"waiting":{},"avg_duration_ns":{},"max_duration_ns":{},"avg_wait_time_ns":{},"max_wait_time_ns":{},"wait_budget_violations":{},"hold_budget_violations":{}}}"#,
+                json_string(&guard.location.to_string()),
+                guard.kind,
+                guard.num_uses,
+                guard.estimated_uses,
+                guard.is_sampled(),
+                guard.num_active_uses(),
+                guard.num_waiting(),
+                guard.avg_duration().as_nanos(),
+                guard.max_duration.as_nanos(),
+                guard.avg_wait_time().as_nanos(),
+                guard.max_wait_time.as_nanos(),
+                guard.wait_budget_violations,
+                guard.hold_budget_violations,
+            );
+        }
+        ret.push_str("]}");
+    }
+    ret.push(']');
+    ret
+}
+
+/// Returns the data on the given locks' guards in the Prometheus text exposition format.
+pub fn to_prometheus(locks: &[LockInfo]) -> String {
+    type Metric = (
+        &'static str,
+        &'static str,
+        &'static str,
+        fn(&GuardInfo) -> f64,
+    );
+    const METRICS: [Metric; 7] = [
+        (
+            "locktick_guard_uses_total",
+            "counter",
+            "The number of times a guard was acquired; estimated if sampled.",
+            |g| g.estimated_uses as f64,
+        ),
+        (
+            "locktick_guard_active",
+            "gauge",
+            "The number of currently active uses of a guard.",
+            |g| g.num_active_uses() as f64,
+        ),
+        (
+            "locktick_guard_waiting",
+            "gauge",
+            "The number of tasks currently waiting to acquire a guard.",
+            |g| g.num_waiting() as f64,
+        ),
+        (
+            "locktick_guard_wait_time_avg_seconds",
+            "gauge",
+            "The moving average of the time spent waiting for a guard.",
+            |g| g.avg_wait_time().as_secs_f64(),
+        ),
+        (
+            "locktick_guard_wait_time_max_seconds",
+            "gauge",
+            "The longest time spent waiting for a guard.",
+            |g| g.max_wait_time.as_secs_f64(),
+        ),
+        (
+            "locktick_guard_duration_avg_seconds",
+            "gauge",
+            "The moving average of the duration of a guard.",
+            |g| g.avg_duration().as_secs_f64(),
+        ),
+        (
+            "locktick_guard_duration_max_seconds",
+            "gauge",
+            "The longest duration of a guard.",
+            |g| g.max_duration.as_secs_f64(),
+        ),
+    ];
+
+    let locks = sorted(locks);
+    let mut ret = String::new();
+    for (name, kind, help, value) in METRICS {
+        let _ = writeln!(ret, "# HELP {name} {help}");
+        let _ = writeln!(ret, "# TYPE {name} {kind}");
+        for lock in &locks {
+            for guard in sorted_guards(lock) {
+                let _ = writeln!(
+                    ret,
+                    r#"{name}{{lock_location="{}",lock_kind="{:?}",guard_location="{}",guard_kind="{:?}"}} {}"#,
+                    label_value(&lock.location.to_string()),
+                    lock.kind,
+                    label_value(&guard.location.to_string()),
+                    guard.kind,
+                    value(guard),
+                );
+            }
+        }
+    }
+    ret
+}
+
+/// Returns a summary of the given locks, ranking the most contended locks,
+/// the longest guard holds, and listing the guards that are still in use;
+/// each of the rankings contains at most `limit` entries.
+pub fn to_summary(locks: &[LockInfo], limit: usize) -> String {
+    let num_guards = locks.iter().map(|l| l.known_guards.len()).sum::<usize>();
+    let mut ret = format!(
+        "locktick summary ({} locks, {num_guards} guards):\n",
+        locks.len()
+    );
+
+    let mut contended = sorted(locks)
+        .into_iter()
+        .map(|lock| {
+            let guards = lock.known_guards.values();
+            let total_wait_time = guards.clone().map(|g| g.total_wait_time).sum::<Duration>();
+            let max_wait_time = guards.clone().map(|g| g.max_wait_time).max();
+            let num_uses = guards.clone().map(|g| g.estimated_uses).sum::<usize>();
+            let num_uses = if guards.clone().any(|g| g.is_sampled()) {
+                format!("~{num_uses} (estimated)")
+            } else {
+                num_uses.to_string()
+            };
+            (
+                lock,
+                total_wait_time,
+                max_wait_time.unwrap_or_default(),
+                num_uses,
+            )
+        })
+        .filter(|(_, total_wait_time, ..)| !total_wait_time.is_zero())
+        .collect::<Vec<_>>();
+    contended.sort_by_key(|(_, total_wait_time, ..)| Reverse(*total_wait_time));
+    let _ = writeln!(ret, "\nthe most contended locks (by total wait time):");
+    for (i, (lock, total, max, num_uses)) in contended.into_iter().take(limit).enumerate() {
+        let _ = writeln!(
+            ret,
+            "{}. {} ({:?}): {total:?} total, {max:?} max across {num_uses} uses",
+            i + 1,
+            lock.location,
+            lock.kind,
+        );
+    }
+
+    let mut holds = sorted(locks)
+        .into_iter()
+        .flat_map(|lock| sorted_guards(lock).into_iter().map(move |g| (lock, g)))
+        .filter(|(_, guard)| !guard.max_duration.is_zero())
+        .collect::<Vec<_>>();
+    holds.sort_by_key(|(_, guard)| Reverse(guard.max_duration));
+    let _ = writeln!(ret, "\nthe longest holds:");
+    for (i, (lock, guard)) in holds.into_iter().take(limit).enumerate() {
+        let _ = writeln!(
+            ret,
+            "{}. {} ({:?}) of {}: {:?} max, {:?} avg, {:?} total",
+            i + 1,
+            guard.location,
+            guard.kind,
+            lock.location,
+            guard.max_duration,
+            guard.avg_duration(),
+            guard.total_duration,
+        );
+    }
+
+    let _ = writeln!(ret, "\nthe guards that are still in use:");
+    ret.push_str(&to_detailed_text(&in_use(locks)));
+
+    ret
+}
+
+/// Returns the given locks, retaining only the guards that are currently in
+/// use, and omitting the locks without any such guards.
+pub fn in_use(locks: &[LockInfo]) -> Vec<LockInfo> {
+    locks
+        .iter()
+        .filter_map(|lock| {
+            let mut lock = lock.clone();
+            lock.known_guards.retain(|_, guard| guard.is_in_use());
+            (!lock.known_guards.is_empty()).then_some(lock)
+        })
+        .collect()
+}
+
+/// Returns the locks that are potentially deadlocked, i.e. the ones that
+/// are currently held, while other tasks are waiting to acquire them.
+pub fn blocked(locks: &[LockInfo]) -> Vec<LockInfo> {
+    in_use(locks)
+        .into_iter()
+        .filter(|lock| {
+            let guards = || lock.known_guards.values();
+            guards().any(|g| g.num_active_uses() != 0) && guards().any(|g| g.num_waiting() != 0)
+        })
+        .collect()
+}
+
+// Returns the locks ordered by their locations.
+fn sorted(locks: &[LockInfo]) -> Vec<&LockInfo> {
+    let mut locks = locks.iter().collect::<Vec<_>>();
+    locks.sort_unstable_by(|l1, l2| l1.location.cmp(&l2.location));
+    locks
+}
+
+// Returns the guards of the given lock ordered by their locations.
+fn sorted_guards(lock: &LockInfo) -> Vec<&GuardInfo> {
+    let mut guards = lock.known_guards.values().collect::<Vec<_>>();
+    guards.sort_unstable_by(|g1, g2| g1.location.cmp(&g2.location));
+    guards
+}
+
+/// Returns the given string as a JSON string literal.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(ret, "\\u{:04x}", c as u32);
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+// Escapes the given string so that it can be used as a Prometheus label value.
+fn label_value(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,129 @@
+use std::sync::RwLock;
+
+use crate::types::{Location, LockKind};
+
+// The currently applicable filters.
+static FILTERS: RwLock<Filters> = RwLock::new(Filters {
+    include_paths: Vec::new(),
+    exclude_paths: Vec::new(),
+    include_modules: Vec::new(),
+    exclude_modules: Vec::new(),
+    include_names: Vec::new(),
+    exclude_names: Vec::new(),
+    include_kinds: Vec::new(),
+    exclude_kinds: Vec::new(),
+});
+
+/// Determines which locks are tracked, based on where they were created, their
+/// names and their kinds. The locks that are excluded aren't registered, and
+/// none of their guards are tracked; a lock is excluded if it matches any of
+/// the exclusion criteria, or if there are inclusion criteria and it doesn't
+/// match any of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filters {
+    /// The prefixes of the paths of the files containing the locks to track.
+    pub include_paths: Vec<String>,
+    /// The prefixes of the paths of the files containing the locks to skip.
+    pub exclude_paths: Vec<String>,
+    /// The modules (e.g. `my_crate::my_module`) containing the locks to track,
+    /// including their submodules.
+    pub include_modules: Vec<String>,
+    /// The modules containing the locks to skip, including their submodules.
+    pub exclude_modules: Vec<String>,
+    /// The names of the locks to track, as provided via `LockOptions`.
+    pub include_names: Vec<String>,
+    /// The names of the locks to skip.
+    pub exclude_names: Vec<String>,
+    /// The kinds of the locks to track.
+    pub include_kinds: Vec<LockKind>,
+    /// The kinds of the locks to skip.
+    pub exclude_kinds: Vec<LockKind>,
+}
+
+impl Filters {
+    // Returns `true` if a lock with the given properties should be tracked;
+    // `function` is the path of the function the lock was created in.
+    fn is_included(
+        &self,
+        location: &Location,
+        function: Option<&str>,
+        name: Option<&str>,
+        kind: LockKind,
+    ) -> bool {
+        let path = location.path.to_string_lossy();
+        let matches =
+            |paths: &[String], modules: &[String], names: &[String], kinds: &[LockKind]| {
+                paths.iter().any(|p| path.starts_with(p.as_str()))
+                    || function.is_some_and(|f| modules.iter().any(|m| in_module(f, m)))
+                    || name.is_some_and(|n| names.iter().any(|m| m == n))
+                    || kinds.contains(&kind)
+            };
+
+        if matches(
+            &self.exclude_paths,
+            &self.exclude_modules,
+            &self.exclude_names,
+            &self.exclude_kinds,
+        ) {
+            return false;
+        }
+
+        let no_inclusions = self.include_paths.is_empty()
+            && self.include_modules.is_empty()
+            && self.include_names.is_empty()
+            && self.include_kinds.is_empty();
+
+        no_inclusions
+            || matches(
+                &self.include_paths,
+                &self.include_modules,
+                &self.include_names,
+                &self.include_kinds,
+            )
+    }
+}
+
+// Returns `true` if the given function path belongs to the given module or
+// one of its submodules; the methods of trait implementations, e.g.
+// `<my_crate::Foo as core::default::Default>::default`, belong to the modules
+// of both the type and the trait.
+fn in_module(function: &str, module: &str) -> bool {
+    let starts_with_module = |path: &str| {
+        path.strip_prefix(module)
+            .is_some_and(|rest| rest.starts_with("::"))
+    };
+
+    match function.strip_prefix('<') {
+        Some(qualified) => match qualified.split_once(" as ") {
+            Some((self_type, trait_path)) => {
+                starts_with_module(self_type) || starts_with_module(trait_path)
+            }
+            None => starts_with_module(qualified),
+        },
+        None => starts_with_module(function),
+    }
+}
+
+/// Sets the filters determining which of the subsequently created locks are
+/// tracked; the locks that were created before remain unaffected.
+pub fn set_filters(filters: Filters) {
+    *FILTERS.write().unwrap() = filters;
+}
+
+/// Returns the currently applicable filters.
+pub fn filters() -> Filters {
+    FILTERS.read().unwrap().clone()
+}
+
+/// Returns `true` if a lock with the given properties should be tracked.
+pub(crate) fn is_included(
+    location: &Location,
+    function: Option<&str>,
+    name: Option<&str>,
+    kind: LockKind,
+) -> bool {
+    FILTERS
+        .read()
+        .unwrap()
+        .is_included(location, function, name, kind)
+}
@@ -0,0 +1,104 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    export::{blocked, in_use, to_json, to_prometheus, to_text},
+    lock_info::{lock_snapshots, reset_lock_stats},
+};
+
+/// Starts a minimal HTTP server on a dedicated thread, and a separate one for
+/// each connection, returning the address it is bound to. It doesn't use any
+/// of the locks tracked by `locktick`, so it remains responsive even if the
+/// rest of the program is deadlocked.
+///
+/// The following routes are available:
+/// - `GET /locks`: all the locks and their guards, as text
+/// - `GET /locks.json`: all the locks and their guards, as JSON
+/// - `GET /metrics`: all the locks and their guards, in the Prometheus format
+/// - `GET /active`: the locks with guards that are currently in use, as text
+/// - `GET /active.json`: the locks with guards that are currently in use, as JSON
+/// - `GET /deadlocks`: the locks that are held while others are waiting for them, as text
+/// - `POST /reset`: resets the statistics related to all the known guards
+pub fn start_server<A: ToSocketAddrs>(addr: A) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    serve(listener)?;
+
+    Ok(local_addr)
+}
+
+// Serves the connections accepted by the given listener on a dedicated thread.
+pub(crate) fn serve(listener: TcpListener) -> io::Result<()> {
+    thread::Builder::new()
+        .name("locktick-http".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // Each connection is handled by its own thread, so that a slow
+                // client doesn't prevent the others from being served. There
+                // is nothing meaningful to be done about a failed response.
+                let _ = thread::Builder::new()
+                    .name("locktick-http-conn".into())
+                    .spawn(move || handle_connection(stream));
+            }
+        })?;
+
+    Ok(())
+}
+
+// Reads a single request and responds to it.
+fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Consume the headers; the request body, if any, is ignored.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split_once('?').map(|(path, _)| path).unwrap_or(path);
+
+    const TEXT: &str = "text/plain; charset=utf-8";
+    const JSON: &str = "application/json";
+    const PROMETHEUS: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/locks") => ("200 OK", TEXT, to_text(&lock_snapshots())),
+        ("GET", "/locks.json") => ("200 OK", JSON, to_json(&lock_snapshots())),
+        ("GET", "/metrics") => ("200 OK", PROMETHEUS, to_prometheus(&lock_snapshots())),
+        ("GET", "/active") => ("200 OK", TEXT, to_text(&in_use(&lock_snapshots()))),
+        ("GET", "/active.json") => ("200 OK", JSON, to_json(&in_use(&lock_snapshots()))),
+        ("GET", "/deadlocks") => ("200 OK", TEXT, to_text(&blocked(&lock_snapshots()))),
+        ("POST", "/reset") => {
+            reset_lock_stats();
+            ("200 OK", TEXT, "the statistics were reset\n".into())
+        }
+        (
+            _,
+            "/locks" | "/locks.json" | "/metrics" | "/active" | "/active.json" | "/deadlocks"
+            | "/reset",
+        ) => (
+            "405 Method Not Allowed",
+            TEXT,
+            "method not allowed\n".into(),
+        ),
+        _ => ("404 Not Found", TEXT, "not found\n".into()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
@@ -1,14 +1,93 @@
+// With the `passthrough` feature, only the wrappers and the types used in their
+// signatures are compiled; all the accounting-related modules are left out.
+#[cfg(not(feature = "passthrough"))]
+mod budget;
+#[cfg(all(feature = "chrome_trace", not(feature = "passthrough")))]
+pub mod chrome_trace;
+#[cfg(all(
+    any(feature = "std", feature = "parking_lot"),
+    not(feature = "passthrough")
+))]
+mod condvar;
+#[cfg(not(feature = "passthrough"))]
+mod config;
+#[cfg(not(feature = "passthrough"))]
+pub mod export;
+#[cfg(not(feature = "passthrough"))]
+mod filter;
+#[cfg(all(feature = "http", not(feature = "passthrough")))]
+pub mod http;
+#[cfg(not(feature = "passthrough"))]
 mod lock_info;
+#[cfg(all(feature = "metrics", not(feature = "passthrough")))]
+mod metrics;
+#[cfg(not(feature = "passthrough"))]
+mod observer;
+#[cfg(all(feature = "opentelemetry", not(feature = "passthrough")))]
+pub mod opentelemetry;
+#[cfg(not(feature = "passthrough"))]
+mod panic;
 #[cfg(feature = "parking_lot")]
+#[cfg_attr(feature = "passthrough", path = "passthrough/parking_lot.rs")]
 pub mod parking_lot;
+#[cfg(feature = "passthrough")]
+mod passthrough;
+#[cfg(all(feature = "pprof", not(feature = "passthrough")))]
+pub mod pprof;
+#[cfg(all(feature = "report", not(feature = "passthrough")))]
+mod report;
+#[cfg(not(feature = "passthrough"))]
+mod sampling;
+#[cfg(all(unix, feature = "signal", not(feature = "passthrough")))]
+pub mod signal;
+#[cfg(all(feature = "stacks", not(feature = "passthrough")))]
+pub mod stacks;
 #[cfg(feature = "std")]
+#[cfg_attr(feature = "passthrough", path = "passthrough/std.rs")]
 pub mod std;
 #[cfg(feature = "tokio")]
+#[cfg_attr(feature = "passthrough", path = "passthrough/tokio.rs")]
 pub mod tokio;
+#[cfg(all(feature = "tracing", not(feature = "passthrough")))]
+pub mod tracing;
+mod types;
+#[cfg(all(unix, feature = "uds", not(feature = "passthrough")))]
+pub mod uds;
 
+// The locations of the locks and guards are obtained from their backtraces.
+#[cfg(not(any(feature = "backtrace", feature = "passthrough")))]
+compile_error!("the `backtrace` feature is required unless the `passthrough` feature is enabled");
+
+#[cfg(not(feature = "passthrough"))]
 pub use lock_info::{
-    lock_snapshots, GuardInfo, GuardKind, Location, LockGuard, LockInfo, LockKind, WaitGuard,
+    is_enabled, lock_snapshots, reset_lock_stats, set_enabled, GuardInfo, LockGuard, LockInfo,
+    Notifications, Permits, WaitGuard,
+};
+#[cfg(feature = "passthrough")]
+pub use passthrough::LockGuard;
+pub use types::{
+    Budget, BudgetKind, BudgetPolicy, BudgetViolation, GuardKind, Location, LockKind, LockOptions,
 };
 
-#[cfg(feature = "test")]
+#[cfg(not(feature = "passthrough"))]
+pub use budget::set_site_budget;
+#[cfg(all(
+    any(feature = "std", feature = "parking_lot"),
+    not(feature = "passthrough")
+))]
+pub use condvar::{condvar_snapshots, CondvarInfo};
+#[cfg(not(feature = "passthrough"))]
+pub use config::{Config, ConfigError};
+#[cfg(not(feature = "passthrough"))]
+pub use filter::{filters, set_filters, Filters};
+#[cfg(not(feature = "passthrough"))]
+pub use observer::{add_observer, clear_observers, LockObserver};
+#[cfg(not(feature = "passthrough"))]
+pub use panic::install_panic_hook;
+#[cfg(all(feature = "report", not(feature = "passthrough")))]
+pub use report::report_on_exit;
+#[cfg(not(feature = "passthrough"))]
+pub use sampling::{sampling, set_sampling, Sampling};
+
+#[cfg(all(feature = "test", not(feature = "passthrough")))]
 pub use lock_info::clear_lock_infos;
@@ -2,11 +2,11 @@ use std::{
     collections::{hash_map::Entry, HashMap},
     fmt,
     ops::{Deref, DerefMut},
-    path::Path,
     sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc, LazyLock, Mutex, RwLock,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        LazyLock, Mutex, RwLock,
     },
+    thread::{self, ThreadId},
     time::{Duration, Instant},
 };
 
@@ -14,6 +14,14 @@ use simple_moving_average::{SingleSumSMA, SMA};
 #[cfg(feature = "tracing")]
 use tracing::trace;
 
+use crate::{
+    sampling::Sampler,
+    types::{
+        BudgetKind, BudgetPolicy, BudgetViolation, GuardKind, Location, LockKind, LockOptions,
+        UNTRACKED_LOCATION,
+    },
+};
+
 // Contains data on all created locks and their guards.
 static LOCK_INFOS: LazyLock<RwLock<HashMap<Location, Mutex<LockInfo>>>> =
     LazyLock::new(|| RwLock::new(HashMap::new()));
@@ -21,22 +29,17 @@ static LOCK_INFOS: LazyLock<RwLock<HashMap<Location, Mutex<LockInfo>>>> =
 // Provides a common source of indices for all the guards.
 static GUARD_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
-/// Points to the filesystem location where a lock or guard was created.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Location {
-    pub path: Arc<Path>,
-    pub line: u32,
-    pub col: u32,
-}
-
-impl fmt::Display for Location {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}@{}:{}", self.path.display(), self.line, self.col)
-    }
-}
+// Indicates whether the guards are currently being tracked.
+static ENABLED: AtomicBool = AtomicBool::new(true);
 
 // Provides the means to procure the location of a lock or its guard.
 pub(crate) fn call_location() -> Location {
+    call_site().0
+}
+
+// Returns the location of a lock or its guard, along with the path of the
+// function it is located in (e.g. `my_crate::my_module::my_function`).
+fn call_site() -> (Location, Option<String>) {
     let backtrace = backtrace::Backtrace::new();
     let frames = backtrace.frames();
     let symbol = frames
@@ -55,12 +58,16 @@ pub(crate) fn call_location() -> Location {
         })
         .unwrap();
     let path = symbol.filename().unwrap().into();
+    // The alternate format skips the hash.
+    let function = symbol.name().map(|name| format!("{name:#}"));
 
-    Location {
+    let location = Location {
         path,
         line: symbol.lineno().unwrap(),
         col: symbol.colno().unwrap(),
-    }
+    };
+
+    (location, function)
 }
 
 /// Returns a vector containing snapshots of the data related to all the locks.
@@ -73,9 +80,47 @@ pub fn lock_snapshots() -> Vec<LockInfo> {
         .collect()
 }
 
+/// Resets the statistics related to all the known guards, e.g. their number of
+/// uses and their average durations; the guards that are currently active or
+/// waited for remain registered.
+pub fn reset_lock_stats() {
+    for info in LOCK_INFOS.read().unwrap().values() {
+        for guard in info.lock().unwrap().known_guards.values_mut() {
+            guard.reset_stats();
+        }
+    }
+}
+
+/// Returns snapshots of the data related to all the locks, unless the data
+/// is currently unavailable, e.g. when it's being modified by this thread.
+pub(crate) fn try_lock_snapshots() -> Option<Vec<LockInfo>> {
+    let lock_infos = LOCK_INFOS.try_read().ok()?;
+    let mut snapshots = Vec::with_capacity(lock_infos.len());
+    for info in lock_infos.values() {
+        snapshots.push(info.try_lock().ok()?.clone());
+    }
+    Some(snapshots)
+}
+
+/// Enables or disables the accounting at runtime; it is enabled by default.
+/// While it is disabled, acquiring a guard doesn't involve any accounting,
+/// and the wrappers behave like the underlying locks; once it is enabled
+/// again, only the subsequently acquired guards are tracked. The creation
+/// of locks is registered regardless.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if the accounting is currently enabled.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
 #[cfg(feature = "test")]
 pub fn clear_lock_infos() {
     LOCK_INFOS.write().unwrap().clear();
+    #[cfg(any(feature = "std", feature = "parking_lot"))]
+    crate::condvar::clear_condvar_infos();
 }
 
 /// Contains all the details related to a given lock, and it can only
@@ -84,34 +129,124 @@ pub fn clear_lock_infos() {
 pub struct LockInfo {
     pub kind: LockKind,
     pub location: Location,
+    pub name: Option<String>,
     pub known_guards: HashMap<Location, GuardInfo>,
+    /// The permits of a semaphore; `None` for the other kinds of locks.
+    pub permits: Option<Permits>,
+    /// The notifications sent via a `Notify`; `None` for the other kinds of locks.
+    pub notifications: Option<Notifications>,
+}
+
+/// The number of permits of a semaphore that are held and available.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Permits {
+    /// The number of permits held by the tracked guards; if only some of the
+    /// acquisitions are tracked (see `set_sampling`), it underestimates the
+    /// number of permits that are actually held.
+    pub held: usize,
+    /// The highest number of permits held by the tracked guards at once.
+    pub max_held: usize,
+    /// The number of available permits, as of the latest tracked acquisition
+    /// or release, or the latest change to the semaphore.
+    pub available: usize,
+}
+
+/// The number of notifications sent via a `Notify`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Notifications {
+    /// The number of calls to `notify_one`.
+    pub notify_one: usize,
+    /// The number of calls to `notify_waiters`.
+    pub notify_waiters: usize,
 }
 
 impl LockInfo {
-    /// Registers the creation of a lock; this is meant to be called
-    /// when creating wrapper objects for different kinds of locks.
-    pub(crate) fn register(kind: LockKind) -> Location {
-        let location = call_location();
+    /// Registers the creation of a lock, unless it is excluded by the filters;
+    /// this is meant to be called when creating wrapper objects for different
+    /// kinds of locks. Returns the location of the lock, and the sampler that
+    /// decides which of its acquisitions are tracked.
+    pub(crate) fn register(kind: LockKind, options: LockOptions) -> (Location, Sampler) {
+        crate::config::init_from_env();
 
-        match LOCK_INFOS.write().unwrap().entry(location.clone()) {
+        let (location, function) = call_site();
+        let name = options.name;
+        if !crate::filter::is_included(&location, function.as_deref(), name.as_deref(), kind) {
+            return (location, Sampler::new(true));
+        }
+
+        let (location, is_new) = match LOCK_INFOS.write().unwrap().entry(location.clone()) {
             Entry::Vacant(entry) => {
                 let info = Mutex::new(Self {
                     kind,
                     location: location.clone(),
+                    name,
                     known_guards: Default::default(),
+                    permits: (kind == LockKind::Semaphore).then(Permits::default),
+                    notifications: (kind == LockKind::Notify).then(Notifications::default),
                 });
 
                 entry.insert(info);
-                location
+                (location, true)
             }
-            Entry::Occupied(entry) => entry.get().lock().unwrap().location.clone(),
+            Entry::Occupied(entry) => (entry.get().lock().unwrap().location.clone(), false),
+        };
+
+        if let Some(budget) = options.budget {
+            crate::budget::set_lock_budget(location.clone(), Some(budget));
+        }
+
+        // Locks created at the same location share their accounting, so only
+        // the first one is reported.
+        if is_new {
+            crate::observer::lock_registered(&location, kind);
+        }
+
+        (location, Sampler::new(false))
+    }
+
+    /// Applies the given update to the permits of the semaphore registered at
+    /// the given location.
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    pub(crate) fn update_permits<F: FnOnce(&mut Permits)>(location: &Location, f: F) {
+        if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(location) {
+            if let Some(permits) = &mut lock_info.lock().unwrap().permits {
+                f(permits);
+            }
+        }
+    }
+
+    /// Applies the given update to the notifications of the `Notify` registered
+    /// at the given location, as long as the accounting is enabled.
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    pub(crate) fn update_notifications<F: FnOnce(&mut Notifications)>(location: &Location, f: F) {
+        if !is_enabled() {
+            return;
+        }
+        if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(location) {
+            if let Some(notifications) = &mut lock_info.lock().unwrap().notifications {
+                f(notifications);
+            }
+        }
+    }
+
+    /// Returns the number of tasks currently waiting to acquire any of the
+    /// guards of the lock, i.e. the length of its queue.
+    pub fn num_waiting(&self) -> usize {
+        self.known_guards.values().map(GuardInfo::num_waiting).sum()
+    }
+
+    // Returns the line describing the lock itself, without its guards.
+    pub(crate) fn heading(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} ({:?}, {name:?}):", self.location, self.kind),
+            None => format!("{} ({:?}):", self.location, self.kind),
         }
     }
 }
 
 impl fmt::Display for LockInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} ({:?}):", self.location, self.kind)?;
+        write!(f, "{}", self.heading())?;
 
         for guard in self.known_guards.values() {
             write!(f, "\n- {guard}")?;
@@ -121,21 +256,29 @@ impl fmt::Display for LockInfo {
     }
 }
 
-/// The type of the lock; either a `Mutex` or an `RwLock`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum LockKind {
-    Mutex,
-    RwLock,
-}
-
 /// A wrapper for the lock guard produced when working with a lock. It
 /// only contains the guard itself and metadata that allows it to be
 /// distinguished from other guards belonging to a single lock.
 pub struct LockGuard<T> {
-    guard: T,
-    pub lock_location: Location,
-    pub guard_location: Location,
-    pub guard_index: usize,
+    // Only `None` once the guard was taken out via `LockGuard::unlock`.
+    guard: Option<T>,
+    lock_location: Location,
+    guard_location: Location,
+    guard_index: usize,
+    #[cfg(feature = "stacks")]
+    stack: Option<std::sync::Arc<crate::stacks::StackEntry>>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing_spans")]
+    span_label: Option<std::sync::Arc<str>>,
+    tracked: bool,
+    // The number of acquisitions represented by this one, due to sampling.
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    weight: usize,
+    // The number of semaphore permits represented by the guard, along with
+    // the semaphore, so that its available permits can be read upon release.
+    #[cfg(feature = "tokio")]
+    permits: Option<(usize, std::sync::Arc<tokio::sync::Semaphore>)>,
 }
 
 impl<T> LockGuard<T> {
@@ -147,78 +290,271 @@ impl<T> LockGuard<T> {
         lock_location: &Location,
         guard_location: Location,
         wait_time: Duration,
+        weight: usize,
     ) -> Self {
-        #[cfg(feature = "tracing")]
-        trace!("Acquired a {:?} guard at {}", guard_kind, guard_location);
+        let guard_index = GUARD_COUNTER.fetch_add(1, Ordering::Relaxed);
 
-        let guard_index = if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(lock_location) {
-            let guard_idx = GUARD_COUNTER.fetch_add(1, Ordering::Relaxed);
-            let mut lock_info = lock_info.lock().unwrap();
-
-            let guard_info = lock_info
-                .known_guards
-                .entry(guard_location.clone())
-                .or_insert_with(|| GuardInfo::new(guard_kind, guard_location.clone()));
-            guard_info.num_uses += 1;
-            guard_info.avg_wait_time.add_sample(wait_time);
-            if wait_time > guard_info.max_wait_time {
-                guard_info.max_wait_time = wait_time;
-            }
-            guard_info.active_uses.insert(guard_idx, Instant::now());
-
-            guard_idx
-        } else {
-            unreachable!();
+        let guard = LockGuard {
+            guard: Some(guard),
+            lock_location: lock_location.clone(),
+            #[cfg(feature = "tracing")]
+            span: crate::tracing::hold_span(
+                lock_location,
+                &guard_location,
+                guard_kind,
+                guard_index,
+                wait_time,
+            ),
+            guard_location,
+            guard_index,
+            // Capturing stacks is expensive, so it's only done for the guards
+            // that have to be waited for (see `from_wait_guard`).
+            #[cfg(feature = "stacks")]
+            stack: None,
+            #[cfg(feature = "tracing_spans")]
+            span_label: crate::tracing::current_span_label(),
+            tracked: true,
+            weight,
+            #[cfg(feature = "tokio")]
+            permits: None,
         };
 
+        guard.record_acquisition(guard_kind, wait_time, false)
+    }
+
+    /// Wraps a guard acquired while the accounting is disabled; it isn't
+    /// registered, and its location and index are placeholders.
+    pub(crate) fn untracked(guard: T, lock_location: &Location) -> Self {
         LockGuard {
-            guard,
+            guard: Some(guard),
             lock_location: lock_location.clone(),
-            guard_location,
-            guard_index,
+            guard_location: UNTRACKED_LOCATION.clone(),
+            guard_index: usize::MAX,
+            #[cfg(feature = "stacks")]
+            stack: None,
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
+            #[cfg(feature = "tracing_spans")]
+            span_label: None,
+            tracked: false,
+            weight: 0,
+            #[cfg(feature = "tokio")]
+            permits: None,
+        }
+    }
+
+    /// Registers the number of permits of the given semaphore represented by
+    /// the guard.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn with_permits(
+        mut self,
+        permits: usize,
+        semaphore: &std::sync::Arc<tokio::sync::Semaphore>,
+    ) -> Self {
+        if self.tracked {
+            LockInfo::update_permits(&self.lock_location, |info| {
+                info.held += permits;
+                info.max_held = info.max_held.max(info.held);
+                info.available = semaphore.available_permits();
+            });
+            self.permits = Some((permits, semaphore.clone()));
+        }
+
+        self
+    }
+
+    /// Stops tracking the guard as if it was dropped, and returns the
+    /// underlying guard, e.g. in order to use the methods specific to it.
+    pub fn into_inner(mut self) -> T {
+        if self.tracked {
+            self.tracked = false;
+            if let Some((_, Some((violation, policy)))) = self.release() {
+                crate::budget::enforce(violation, policy);
+            }
         }
+
+        self.guard.take().unwrap()
+    }
+
+    /// Returns `true` if the guard is tracked, i.e. it was acquired while
+    /// the accounting was enabled.
+    pub fn is_tracked(&self) -> bool {
+        self.tracked
+    }
+
+    /// Returns the location of the lock the guard belongs to.
+    pub fn lock_location(&self) -> &Location {
+        &self.lock_location
+    }
+
+    /// Returns the location where the guard was acquired; it is a placeholder
+    /// if the guard isn't tracked.
+    pub fn guard_location(&self) -> &Location {
+        &self.guard_location
+    }
+
+    /// Returns the index of the guard, unique across all the guards; it is
+    /// `usize::MAX` if the guard isn't tracked.
+    pub fn guard_index(&self) -> usize {
+        self.guard_index
     }
 
     /// Registers the creation of a guard from a WaitGuard, reusing the wait index.
     /// This is called when a waiting task successfully acquires the lock.
-    pub(crate) fn from_wait_guard(guard: T, wait_guard: WaitGuard, wait_time: Duration) -> Self {
+    pub(crate) fn from_wait_guard(
+        guard: T,
+        #[allow(unused_mut)] mut wait_guard: WaitGuard,
+        wait_time: Duration,
+    ) -> Self {
         let guard_kind = wait_guard.guard_kind;
         let lock_location = wait_guard.lock_location.clone();
         let guard_location = wait_guard.guard_location.clone();
         let guard_index = wait_guard.wait_index;
+        let weight = wait_guard.weight;
+        #[cfg(feature = "stacks")]
+        let stack = wait_guard.stack.take();
+        #[cfg(feature = "tracing_spans")]
+        let span_label = wait_guard.span_label.take();
+        #[cfg(feature = "stacks")]
+        if let Some(stack) = &stack {
+            stack.add_wait(wait_time);
+        }
+        #[cfg(feature = "tracing")]
+        let span = {
+            let wait_span = std::mem::replace(&mut wait_guard.span, tracing::Span::none());
+            wait_span.record("wait_time_ns", wait_time.as_nanos() as u64);
+            let span = crate::tracing::hold_span(
+                &lock_location,
+                &guard_location,
+                guard_kind,
+                guard_index,
+                wait_time,
+            );
+            span.follows_from(&wait_span);
+            span
+        };
 
         // Consume the wait guard without running its Drop impl
         wait_guard.finish();
 
+        let guard = LockGuard {
+            guard: Some(guard),
+            lock_location,
+            guard_location,
+            guard_index,
+            #[cfg(feature = "stacks")]
+            stack,
+            #[cfg(feature = "tracing")]
+            span,
+            #[cfg(feature = "tracing_spans")]
+            span_label,
+            tracked: true,
+            weight,
+            #[cfg(feature = "tokio")]
+            permits: None,
+        };
+
+        guard.record_acquisition(guard_kind, wait_time, true)
+    }
+
+    // Registers the acquisition of a tracked guard, preceded by a wait with
+    // the same index if `waited` is `true`, and enforces the wait budget.
+    fn record_acquisition(self, guard_kind: GuardKind, wait_time: Duration, waited: bool) -> Self {
+        let lock_location = &self.lock_location;
+        let guard_location = &self.guard_location;
+        let guard_index = self.guard_index;
+
         #[cfg(feature = "tracing")]
         trace!("Acquired a {:?} guard at {}", guard_kind, guard_location);
 
-        if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(&lock_location) {
-            let mut lock_info = lock_info.lock().unwrap();
+        let (_lock_kind, _wait_timestamp, budget_violation) =
+            if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(lock_location) {
+                let mut lock_info = lock_info.lock().unwrap();
 
-            let guard_info = lock_info
-                .known_guards
-                .entry(guard_location.clone())
-                .or_insert_with(|| GuardInfo::new(guard_kind, guard_location.clone()));
+                let guard_info = lock_info
+                    .known_guards
+                    .entry(guard_location.clone())
+                    .or_insert_with(|| GuardInfo::new(guard_kind, guard_location.clone()));
 
-            // Remove from waiting, add to active
-            guard_info.waiting_tasks.remove(&guard_index);
-            guard_info.num_uses += 1;
-            guard_info.avg_wait_time.add_sample(wait_time);
-            if wait_time > guard_info.max_wait_time {
-                guard_info.max_wait_time = wait_time;
-            }
-            guard_info.active_uses.insert(guard_index, Instant::now());
-        } else {
-            unreachable!();
+                // Remove from waiting, add to active
+                let wait_timestamp = waited
+                    .then(|| guard_info.waiting_tasks.remove(&guard_index))
+                    .flatten()
+                    .map(|wait| wait.timestamp);
+                guard_info.add_use(self.weight, wait_time);
+                guard_info.active_uses.insert(guard_index, GuardUse::new());
+                let budget_violation = crate::budget::check(
+                    BudgetKind::Wait,
+                    lock_location,
+                    guard_location,
+                    guard_kind,
+                    guard_index,
+                    wait_time,
+                );
+                if budget_violation.is_some() {
+                    guard_info.wait_budget_violations += 1;
+                }
+                #[cfg(feature = "tracing_spans")]
+                if let Some(span_label) = &self.span_label {
+                    guard_info
+                        .span_stats
+                        .entry(span_label.clone())
+                        .or_default()
+                        .add_wait(wait_time);
+                }
+
+                (lock_info.kind, wait_timestamp, budget_violation)
+            } else {
+                unreachable!();
+            };
+
+        #[cfg(feature = "chrome_trace")]
+        if let Some(wait_timestamp) = _wait_timestamp {
+            crate::chrome_trace::record(
+                crate::chrome_trace::EventKind::Wait,
+                lock_location,
+                guard_location,
+                guard_kind,
+                guard_index,
+                wait_timestamp,
+                wait_timestamp.elapsed(),
+            );
         }
 
-        LockGuard {
-            guard,
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_acquisition(
+            _lock_kind,
             lock_location,
+            guard_kind,
             guard_location,
+            wait_time,
+            self.weight,
+        );
+
+        #[cfg(feature = "tracing")]
+        crate::tracing::check_wait(
+            lock_location,
+            guard_location,
+            guard_kind,
             guard_index,
+            wait_time,
+        );
+
+        crate::observer::guard_acquired(
+            lock_location,
+            guard_location,
+            guard_kind,
+            guard_index,
+            wait_time,
+        );
+
+        // The guard is already constructed, so that it is released if the
+        // enforcement panics.
+        if let Some((violation, policy)) = budget_violation {
+            crate::budget::enforce(violation, policy);
         }
+
+        self
     }
 }
 
@@ -229,6 +565,13 @@ pub struct WaitGuard {
     pub(crate) guard_location: Location,
     pub(crate) guard_kind: GuardKind,
     pub(crate) wait_index: usize,
+    #[cfg(feature = "stacks")]
+    stack: Option<std::sync::Arc<crate::stacks::StackEntry>>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing_spans")]
+    span_label: Option<std::sync::Arc<str>>,
+    weight: usize,
     finished: bool,
 }
 
@@ -238,6 +581,7 @@ impl WaitGuard {
         guard_kind: GuardKind,
         lock_location: &Location,
         guard_location: Location,
+        weight: usize,
     ) -> Self {
         #[cfg(feature = "tracing")]
         trace!(
@@ -255,20 +599,129 @@ impl WaitGuard {
                 .known_guards
                 .entry(guard_location.clone())
                 .or_insert_with(|| GuardInfo::new(guard_kind, guard_location.clone()));
-            guard_info.waiting_tasks.insert(wait_index, Instant::now());
+            guard_info.waiting_tasks.insert(wait_index, GuardUse::new());
         } else {
             unreachable!();
         }
 
+        crate::observer::wait_started(lock_location, &guard_location, guard_kind, wait_index);
+
         WaitGuard {
             lock_location: lock_location.clone(),
+            #[cfg(feature = "tracing")]
+            span: crate::tracing::wait_span(lock_location, &guard_location, guard_kind, wait_index),
             guard_location,
             guard_kind,
             wait_index,
+            #[cfg(feature = "stacks")]
+            stack: crate::stacks::capture(),
+            #[cfg(feature = "tracing_spans")]
+            span_label: crate::tracing::current_span_label(),
+            weight,
             finished: false,
         }
     }
 
+    /// Registers the end of a wait that doesn't result in a guard being held,
+    /// e.g. one for a notification, as a use of its guard.
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    pub(crate) fn complete(mut self, wait_time: Duration) {
+        self.finished = true;
+
+        #[cfg(feature = "tracing")]
+        self.span
+            .record("wait_time_ns", wait_time.as_nanos() as u64);
+        #[cfg(feature = "stacks")]
+        if let Some(stack) = &self.stack {
+            stack.add_wait(wait_time);
+        }
+
+        let lock_location = &self.lock_location;
+        let guard_location = &self.guard_location;
+        let (_lock_kind, _wait_timestamp, budget_violation) =
+            if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(lock_location) {
+                let mut lock_info = lock_info.lock().unwrap();
+                let lock_kind = lock_info.kind;
+
+                let guard_info = lock_info
+                    .known_guards
+                    .entry(guard_location.clone())
+                    .or_insert_with(|| GuardInfo::new(self.guard_kind, guard_location.clone()));
+                let wait_timestamp = guard_info
+                    .waiting_tasks
+                    .remove(&self.wait_index)
+                    .map(|wait| wait.timestamp);
+                guard_info.add_use(self.weight, wait_time);
+                let budget_violation = crate::budget::check(
+                    BudgetKind::Wait,
+                    lock_location,
+                    guard_location,
+                    self.guard_kind,
+                    self.wait_index,
+                    wait_time,
+                );
+                if budget_violation.is_some() {
+                    guard_info.wait_budget_violations += 1;
+                }
+                #[cfg(feature = "tracing_spans")]
+                if let Some(span_label) = &self.span_label {
+                    guard_info
+                        .span_stats
+                        .entry(span_label.clone())
+                        .or_default()
+                        .add_wait(wait_time);
+                }
+
+                (lock_kind, wait_timestamp, budget_violation)
+            } else {
+                unreachable!();
+            };
+
+        #[cfg(feature = "chrome_trace")]
+        if let Some(wait_timestamp) = _wait_timestamp {
+            crate::chrome_trace::record(
+                crate::chrome_trace::EventKind::Wait,
+                lock_location,
+                guard_location,
+                self.guard_kind,
+                self.wait_index,
+                wait_timestamp,
+                wait_timestamp.elapsed(),
+            );
+        }
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_acquisition(
+            _lock_kind,
+            lock_location,
+            self.guard_kind,
+            guard_location,
+            wait_time,
+            self.weight,
+        );
+
+        #[cfg(feature = "tracing")]
+        crate::tracing::check_wait(
+            lock_location,
+            guard_location,
+            self.guard_kind,
+            self.wait_index,
+            wait_time,
+        );
+
+        crate::observer::guard_acquired(
+            lock_location,
+            guard_location,
+            self.guard_kind,
+            self.wait_index,
+            wait_time,
+        );
+
+        if let Some((violation, policy)) = budget_violation {
+            crate::budget::enforce(violation, policy);
+        }
+    }
+
     /// Marks this WaitGuard as finished, preventing the Drop impl from running.
     /// This should be called when the lock has been successfully acquired.
     pub(crate) fn finish(mut self) {
@@ -289,11 +742,83 @@ impl Drop for WaitGuard {
             self.guard_location
         );
 
-        if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(&self.lock_location) {
+        let cancelled = if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(&self.lock_location)
+        {
             let mut lock_info = lock_info.lock().unwrap();
             if let Some(guard_info) = lock_info.known_guards.get_mut(&self.guard_location) {
-                guard_info.waiting_tasks.remove(&self.wait_index);
+                let _wait_timestamp = guard_info
+                    .waiting_tasks
+                    .remove(&self.wait_index)
+                    .map(|wait| wait.timestamp);
+                #[cfg(feature = "tracing")]
+                if let Some(wait_timestamp) = _wait_timestamp {
+                    let wait_time = wait_timestamp.elapsed().as_nanos() as u64;
+                    self.span.record("wait_time_ns", wait_time);
+                    self.span.record("cancelled", true);
+                }
+                #[cfg(feature = "stacks")]
+                if let (Some(stack), Some(wait_timestamp)) = (&self.stack, _wait_timestamp) {
+                    stack.add_wait(wait_timestamp.elapsed());
+                }
+                let budget_violation = _wait_timestamp.and_then(|wait_timestamp| {
+                    crate::budget::check(
+                        BudgetKind::Wait,
+                        &self.lock_location,
+                        &self.guard_location,
+                        self.guard_kind,
+                        self.wait_index,
+                        wait_timestamp.elapsed(),
+                    )
+                });
+                if budget_violation.is_some() {
+                    guard_info.wait_budget_violations += 1;
+                }
+                _wait_timestamp.map(|wait_timestamp| (wait_timestamp, budget_violation))
+            } else {
+                None
             }
+        } else {
+            None
+        };
+
+        if let Some((wait_timestamp, budget_violation)) = cancelled {
+            #[cfg(feature = "chrome_trace")]
+            crate::chrome_trace::record(
+                crate::chrome_trace::EventKind::CancelledWait,
+                &self.lock_location,
+                &self.guard_location,
+                self.guard_kind,
+                self.wait_index,
+                wait_timestamp,
+                wait_timestamp.elapsed(),
+            );
+            crate::observer::wait_cancelled(
+                &self.lock_location,
+                &self.guard_location,
+                self.guard_kind,
+                self.wait_index,
+                wait_timestamp.elapsed(),
+            );
+
+            if let Some((violation, policy)) = budget_violation {
+                crate::budget::enforce(violation, policy);
+            }
+        }
+    }
+}
+
+// A single use of a guard, i.e. either holding it or waiting to acquire it.
+#[derive(Debug, Clone, Copy)]
+struct GuardUse {
+    timestamp: Instant,
+    thread: ThreadId,
+}
+
+impl GuardUse {
+    fn new() -> Self {
+        Self {
+            timestamp: Instant::now(),
+            thread: thread::current().id(),
         }
     }
 }
@@ -304,12 +829,28 @@ pub struct GuardInfo {
     pub kind: GuardKind,
     pub location: Location,
     pub num_uses: usize,
-    active_uses: HashMap<usize, Instant>,
-    waiting_tasks: HashMap<usize, Instant>,
+    /// The estimated number of uses; it differs from `num_uses` only if
+    /// some of the uses weren't sampled (see `Sampling`).
+    pub estimated_uses: usize,
+    // The estimated number of uses, unaffected by `reset_lock_stats`.
+    #[cfg_attr(not(feature = "opentelemetry"), allow(dead_code))]
+    pub(crate) lifetime_uses: usize,
+    active_uses: HashMap<usize, GuardUse>,
+    waiting_tasks: HashMap<usize, GuardUse>,
     avg_wait_time: SingleSumSMA<Duration, u32, 50>,
     pub max_wait_time: Duration,
+    pub total_wait_time: Duration,
     avg_duration: SingleSumSMA<Duration, u32, 50>,
     pub max_duration: Duration,
+    pub total_duration: Duration,
+    /// The number of waits that exceeded the applicable `Budget`.
+    pub wait_budget_violations: usize,
+    /// The number of uses that exceeded the hold time of the applicable `Budget`.
+    pub hold_budget_violations: usize,
+    /// The statistics related to the uses of the guard within the spans
+    /// identified by a `tracing::SpanLayer`.
+    #[cfg(feature = "tracing_spans")]
+    pub span_stats: HashMap<std::sync::Arc<str>, crate::tracing::SpanStats>,
 }
 
 impl GuardInfo {
@@ -318,15 +859,58 @@ impl GuardInfo {
             kind,
             location,
             num_uses: 0,
+            estimated_uses: 0,
+            lifetime_uses: 0,
             active_uses: Default::default(),
             waiting_tasks: Default::default(),
             avg_wait_time: SingleSumSMA::from_zero(Duration::ZERO),
             max_wait_time: Duration::ZERO,
+            total_wait_time: Duration::ZERO,
             avg_duration: SingleSumSMA::from_zero(Duration::ZERO),
             max_duration: Duration::ZERO,
+            total_duration: Duration::ZERO,
+            wait_budget_violations: 0,
+            hold_budget_violations: 0,
+            #[cfg(feature = "tracing_spans")]
+            span_stats: Default::default(),
+        }
+    }
+
+    // Registers a use of the guard, representing `weight` acquisitions, which
+    // involved waiting for the given time.
+    fn add_use(&mut self, weight: usize, wait_time: Duration) {
+        self.num_uses += 1;
+        self.estimated_uses += weight;
+        self.lifetime_uses += weight;
+        self.avg_wait_time.add_sample(wait_time);
+        self.total_wait_time += wait_time;
+        if wait_time > self.max_wait_time {
+            self.max_wait_time = wait_time;
         }
     }
 
+    // Resets the statistics, retaining the current uses.
+    fn reset_stats(&mut self) {
+        self.num_uses = 0;
+        self.estimated_uses = 0;
+        self.avg_wait_time = SingleSumSMA::from_zero(Duration::ZERO);
+        self.max_wait_time = Duration::ZERO;
+        self.total_wait_time = Duration::ZERO;
+        self.avg_duration = SingleSumSMA::from_zero(Duration::ZERO);
+        self.max_duration = Duration::ZERO;
+        self.total_duration = Duration::ZERO;
+        self.wait_budget_violations = 0;
+        self.hold_budget_violations = 0;
+        #[cfg(feature = "tracing_spans")]
+        self.span_stats.clear();
+    }
+
+    /// Returns `true` if only some of the uses of the guard were tracked, in
+    /// which case `estimated_uses` is an estimate.
+    pub fn is_sampled(&self) -> bool {
+        self.estimated_uses != self.num_uses
+    }
+
     /// Returns `true` if threads are currently holding or waiting for this guard.
     pub fn is_in_use(&self) -> bool {
         !self.active_uses.is_empty() || !self.waiting_tasks.is_empty()
@@ -347,6 +931,30 @@ impl GuardInfo {
         indices
     }
 
+    /// Returns the call indices of the currently active guards, along with the
+    /// time that has passed since they were acquired.
+    pub fn active_durations(&self) -> Vec<(usize, Duration)> {
+        let mut durations = self
+            .active_uses
+            .iter()
+            .map(|(idx, guard_use)| (*idx, guard_use.timestamp.elapsed()))
+            .collect::<Vec<_>>();
+        durations.sort_unstable_by_key(|(idx, _)| *idx);
+        durations
+    }
+
+    /// Returns the call indices of the guards currently held by the given thread.
+    pub fn held_by(&self, thread: ThreadId) -> Vec<usize> {
+        let mut indices = self
+            .active_uses
+            .iter()
+            .filter(|(_, guard_use)| guard_use.thread == thread)
+            .map(|(idx, _)| *idx)
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices
+    }
+
     /// Returns the number of tasks currently waiting to acquire this guard.
     pub fn num_waiting(&self) -> usize {
         self.waiting_tasks.len()
@@ -360,6 +968,30 @@ impl GuardInfo {
         indices
     }
 
+    /// Returns the call indices of the currently waiting tasks, along with
+    /// the time that has passed since they started waiting.
+    pub fn waiting_durations(&self) -> Vec<(usize, Duration)> {
+        let mut durations = self
+            .waiting_tasks
+            .iter()
+            .map(|(idx, guard_use)| (*idx, guard_use.timestamp.elapsed()))
+            .collect::<Vec<_>>();
+        durations.sort_unstable_by_key(|(idx, _)| *idx);
+        durations
+    }
+
+    /// Returns the call indices of the waits currently performed by the given thread.
+    pub fn awaited_by(&self, thread: ThreadId) -> Vec<usize> {
+        let mut indices = self
+            .waiting_tasks
+            .iter()
+            .filter(|(_, guard_use)| guard_use.thread == thread)
+            .map(|(idx, _)| *idx)
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices
+    }
+
     /// Returns the average wait time for the guard. It is a moving
     /// average that gets updated with each use.
     pub fn avg_wait_time(&self) -> Duration {
@@ -375,6 +1007,15 @@ impl GuardInfo {
 
 impl fmt::Display for GuardInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let calls = if self.is_sampled() {
+            format!(
+                "~{} (estimated from {} samples)",
+                self.estimated_uses, self.num_uses
+            )
+        } else {
+            self.num_uses.to_string()
+        };
+
         write!(
             f,
             "{} ({:?}): curr users: {}; waiting: {}; calls: {}; duration: {:?} avg, {:?} max; wait: {:?} avg, {:?} max",
@@ -382,7 +1023,7 @@ impl fmt::Display for GuardInfo {
             self.kind,
             self.active_uses.len(),
             self.waiting_tasks.len(),
-            self.num_uses,
+            calls,
             self.avg_duration.get_average(),
             self.max_duration,
             self.avg_wait_time.get_average(),
@@ -395,33 +1036,159 @@ impl<T: Deref> Deref for LockGuard<T> {
     type Target = T::Target;
 
     fn deref(&self) -> &Self::Target {
-        self.guard.deref()
+        self.guard.as_ref().unwrap().deref()
     }
 }
 
 impl<T: DerefMut> DerefMut for LockGuard<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.guard.deref_mut()
+        self.guard.as_mut().unwrap().deref_mut()
     }
 }
 
 impl<T> Drop for LockGuard<T> {
     fn drop(&mut self) {
+        if !self.tracked {
+            return;
+        }
+
+        // Return the permits first, so that the available ones can be read.
+        #[cfg(feature = "tokio")]
+        if self.permits.is_some() {
+            drop(self.guard.take());
+        }
+
+        if let Some((_, Some((violation, policy)))) = self.release() {
+            // Release the underlying guard first, so that the lock isn't
+            // poisoned if the enforcement panics.
+            drop(self.guard.take());
+            crate::budget::enforce(violation, policy);
+        }
+    }
+}
+
+/// The details needed to track a guard again once its lock is reacquired,
+/// after it was temporarily released, e.g. by a condition variable.
+pub(crate) struct Relock {
+    lock_location: Location,
+    // Registers the task as waiting to reacquire the guard, if it is tracked.
+    wait_guard: Option<WaitGuard>,
+}
+
+impl Relock {
+    /// Wraps the reacquired guard, registering it as a new use of the original
+    /// guard's site; `wait_time` is the time spent reacquiring the lock.
+    pub(crate) fn relock<T>(self, guard: T, wait_time: Duration) -> LockGuard<T> {
+        match self.wait_guard {
+            Some(wait_guard) => LockGuard::from_wait_guard(guard, wait_guard, wait_time),
+            None => LockGuard::untracked(guard, &self.lock_location),
+        }
+    }
+}
+
+impl<T> LockGuard<T> {
+    /// Releases the guard as if it was dropped, but returns the underlying
+    /// guard instead of dropping it, along with the means to track it again
+    /// once it is reacquired; until then, the task is registered as waiting
+    /// for the guard.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn unlock(mut self) -> (T, Relock) {
+        let relock = self.prepare_relock();
+        (self.guard.take().unwrap(), relock)
+    }
+
+    /// Releases the guard for the duration of the given function, which is
+    /// provided with the underlying guard, e.g. in order to wait on a condition
+    /// variable, and returns the time it spent reacquiring the lock; the guard
+    /// is then tracked as a new use of the same site.
+    #[cfg_attr(not(feature = "parking_lot"), allow(dead_code))]
+    pub(crate) fn unlocked<R, F: FnOnce(&mut T) -> (R, Duration)>(&mut self, f: F) -> R {
+        let relock = self.prepare_relock();
+        let (ret, wait_time) = f(self.guard.as_mut().unwrap());
+        let guard = self.guard.take().unwrap();
+        *self = relock.relock(guard, wait_time);
+
+        ret
+    }
+
+    // Performs the accounting related to releasing the guard, and stops
+    // tracking it.
+    fn prepare_relock(&mut self) -> Relock {
+        let guard_kind = if self.tracked {
+            self.tracked = false;
+            self.release().map(|(guard_kind, budget_violation)| {
+                if let Some((violation, policy)) = budget_violation {
+                    crate::budget::enforce(violation, policy);
+                }
+                guard_kind
+            })
+        } else {
+            None
+        };
+
+        Relock {
+            lock_location: self.lock_location.clone(),
+            wait_guard: guard_kind.map(|guard_kind| {
+                WaitGuard::new(
+                    guard_kind,
+                    &self.lock_location,
+                    self.guard_location.clone(),
+                    self.weight,
+                )
+            }),
+        }
+    }
+
+    // Performs the accounting related to releasing the guard, and returns its
+    // kind along with the hold budget violation to be enforced, if it was
+    // registered.
+    fn release(&mut self) -> Option<(GuardKind, Option<(BudgetViolation, BudgetPolicy)>)> {
         let timestamp = Instant::now();
 
-        if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(&self.lock_location) {
+        let released = if let Some(lock_info) = LOCK_INFOS.read().unwrap().get(&self.lock_location)
+        {
             let mut lock_info = lock_info.lock().unwrap();
+            let lock_kind = lock_info.kind;
+            #[cfg(feature = "tokio")]
+            if let (Some(permits), Some((held, semaphore))) =
+                (&mut lock_info.permits, &self.permits)
+            {
+                permits.held = permits.held.saturating_sub(*held);
+                permits.available = semaphore.available_permits();
+            }
             let known_guard = lock_info
                 .known_guards
                 .get_mut(&self.guard_location)
                 .unwrap();
-            let guard_timestamp = known_guard.active_uses.remove(&self.guard_index).unwrap();
+            let guard_timestamp = known_guard
+                .active_uses
+                .remove(&self.guard_index)
+                .unwrap()
+                .timestamp;
             let duration = timestamp - guard_timestamp;
             known_guard.avg_duration.add_sample(duration);
+            known_guard.total_duration += duration;
             if duration > known_guard.max_duration {
                 known_guard.max_duration = duration;
             }
 
+            #[cfg(feature = "stacks")]
+            if let Some(stack) = &self.stack {
+                stack.add_hold(duration);
+            }
+
+            #[cfg(feature = "tracing_spans")]
+            if let Some(span_stats) = self
+                .span_label
+                .as_ref()
+                .and_then(|span_label| known_guard.span_stats.get_mut(span_label))
+            {
+                span_stats.add_hold(duration);
+            }
+
+            #[cfg(feature = "tracing")]
+            self.span.record("duration_ns", duration.as_nanos() as u64);
+
             #[cfg(feature = "tracing")]
             trace!(
                 "The {:?} guard for lock {} acquired at {} was dropped after {:?}",
@@ -430,14 +1197,74 @@ impl<T> Drop for LockGuard<T> {
                 known_guard.location,
                 duration,
             );
+
+            let budget_violation = crate::budget::check(
+                BudgetKind::Hold,
+                &self.lock_location,
+                &self.guard_location,
+                known_guard.kind,
+                self.guard_index,
+                duration,
+            );
+            if budget_violation.is_some() {
+                known_guard.hold_budget_violations += 1;
+            }
+
+            Some((
+                lock_kind,
+                known_guard.kind,
+                guard_timestamp,
+                duration,
+                budget_violation,
+            ))
+        } else {
+            None
+        };
+
+        if let Some((_lock_kind, guard_kind, _guard_timestamp, duration, budget_violation)) =
+            released
+        {
+            #[cfg(feature = "chrome_trace")]
+            crate::chrome_trace::record(
+                crate::chrome_trace::EventKind::Hold,
+                &self.lock_location,
+                &self.guard_location,
+                guard_kind,
+                self.guard_index,
+                _guard_timestamp,
+                duration,
+            );
+
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_release(
+                _lock_kind,
+                &self.lock_location,
+                guard_kind,
+                &self.guard_location,
+                duration,
+                self.weight,
+            );
+
+            #[cfg(feature = "tracing")]
+            crate::tracing::check_hold(
+                &self.lock_location,
+                &self.guard_location,
+                guard_kind,
+                self.guard_index,
+                duration,
+            );
+
+            crate::observer::guard_released(
+                &self.lock_location,
+                &self.guard_location,
+                guard_kind,
+                self.guard_index,
+                duration,
+            );
+
+            Some((guard_kind, budget_violation))
+        } else {
+            None
         }
     }
 }
-
-/// The type of the guard that was created when working with a lock.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum GuardKind {
-    Lock,
-    Read,
-    Write,
-}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use metrics::{counter, histogram};
+
+use crate::types::{GuardKind, Location, LockKind};
+
+/// Records the acquisition of a guard using the globally installed recorder;
+/// the weight is the number of acquisitions it represents, due to sampling.
+pub(crate) fn record_acquisition(
+    lock_kind: LockKind,
+    lock_location: &Location,
+    guard_kind: GuardKind,
+    guard_location: &Location,
+    wait_time: Duration,
+    weight: usize,
+) {
+    let labels = labels(lock_kind, lock_location, guard_kind, guard_location);
+    counter!("locktick.guard.acquired", &labels).increment(weight as u64);
+    histogram!("locktick.guard.wait_time", &labels).record(wait_time);
+}
+
+/// Records the release of a guard using the globally installed recorder.
+pub(crate) fn record_release(
+    lock_kind: LockKind,
+    lock_location: &Location,
+    guard_kind: GuardKind,
+    guard_location: &Location,
+    duration: Duration,
+    weight: usize,
+) {
+    let labels = labels(lock_kind, lock_location, guard_kind, guard_location);
+    counter!("locktick.guard.released", &labels).increment(weight as u64);
+    histogram!("locktick.guard.duration", &labels).record(duration);
+}
+
+// Returns the labels identifying the given guard.
+fn labels(
+    lock_kind: LockKind,
+    lock_location: &Location,
+    guard_kind: GuardKind,
+    guard_location: &Location,
+) -> [(&'static str, String); 4] {
+    [
+        ("lock.location", lock_location.to_string()),
+        ("lock.kind", format!("{lock_kind:?}")),
+        ("guard.location", guard_location.to_string()),
+        ("guard.kind", format!("{guard_kind:?}")),
+    ]
+}
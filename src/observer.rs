@@ -0,0 +1,183 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+use crate::types::{GuardKind, Location, LockKind};
+
+// All the registered observers.
+static OBSERVERS: RwLock<Vec<Arc<dyn LockObserver>>> = RwLock::new(Vec::new());
+
+// Allows the observers to be skipped without locking if there are none.
+static HAS_OBSERVERS: AtomicBool = AtomicBool::new(false);
+
+/// Allows custom logic to be plugged into the lifecycle of locks and their
+/// guards; all the methods do nothing by default. The callbacks are invoked
+/// on the thread that performs the related operation, once the accounting
+/// related to it is complete, so they are free to inspect the lock data
+/// (e.g. via `lock_snapshots`), but they shouldn't block.
+pub trait LockObserver: Send + Sync {
+    /// Called when a lock is first created at its location; the locks created
+    /// at the same location later on share its accounting, and aren't reported.
+    fn lock_registered(&self, _lock_location: &Location, _lock_kind: LockKind) {}
+
+    /// Called when a guard can't be acquired immediately, and the task
+    /// starts waiting for it.
+    fn wait_started(
+        &self,
+        _lock_location: &Location,
+        _guard_location: &Location,
+        _guard_kind: GuardKind,
+        _wait_index: usize,
+    ) {
+    }
+
+    /// Called when a task stops waiting for a guard without acquiring it,
+    /// e.g. when the related future is dropped.
+    fn wait_cancelled(
+        &self,
+        _lock_location: &Location,
+        _guard_location: &Location,
+        _guard_kind: GuardKind,
+        _wait_index: usize,
+        _wait_time: Duration,
+    ) {
+    }
+
+    /// Called when a guard is acquired; for guards that had to be waited
+    /// for, the guard index is the same as the wait index.
+    fn guard_acquired(
+        &self,
+        _lock_location: &Location,
+        _guard_location: &Location,
+        _guard_kind: GuardKind,
+        _guard_index: usize,
+        _wait_time: Duration,
+    ) {
+    }
+
+    /// Called when an attempt to acquire a guard without waiting fails.
+    fn try_lock_failed(
+        &self,
+        _lock_location: &Location,
+        _guard_location: &Location,
+        _guard_kind: GuardKind,
+    ) {
+    }
+
+    /// Called when a guard is dropped.
+    fn guard_released(
+        &self,
+        _lock_location: &Location,
+        _guard_location: &Location,
+        _guard_kind: GuardKind,
+        _guard_index: usize,
+        _duration: Duration,
+    ) {
+    }
+}
+
+/// Registers an observer, which will be notified of all the subsequent
+/// events related to locks and their guards.
+pub fn add_observer(observer: Arc<dyn LockObserver>) {
+    let mut observers = OBSERVERS.write().unwrap();
+    observers.push(observer);
+    HAS_OBSERVERS.store(true, Ordering::Relaxed);
+}
+
+/// Unregisters all the observers.
+pub fn clear_observers() {
+    let mut observers = OBSERVERS.write().unwrap();
+    observers.clear();
+    HAS_OBSERVERS.store(false, Ordering::Relaxed);
+}
+
+// Invokes the given callback for all the registered observers.
+fn notify<F: Fn(&dyn LockObserver)>(callback: F) {
+    if !HAS_OBSERVERS.load(Ordering::Relaxed) {
+        return;
+    }
+    // Clone the observers, so that they are free to register others.
+    let observers = OBSERVERS.read().unwrap().clone();
+    for observer in observers {
+        callback(&*observer);
+    }
+}
+
+pub(crate) fn lock_registered(lock_location: &Location, lock_kind: LockKind) {
+    notify(|observer| observer.lock_registered(lock_location, lock_kind));
+}
+
+pub(crate) fn wait_started(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    wait_index: usize,
+) {
+    notify(|observer| observer.wait_started(lock_location, guard_location, guard_kind, wait_index));
+}
+
+pub(crate) fn wait_cancelled(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    wait_index: usize,
+    wait_time: Duration,
+) {
+    notify(|observer| {
+        observer.wait_cancelled(
+            lock_location,
+            guard_location,
+            guard_kind,
+            wait_index,
+            wait_time,
+        )
+    });
+}
+
+pub(crate) fn guard_acquired(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    wait_time: Duration,
+) {
+    notify(|observer| {
+        observer.guard_acquired(
+            lock_location,
+            guard_location,
+            guard_kind,
+            guard_index,
+            wait_time,
+        )
+    });
+}
+
+pub(crate) fn try_lock_failed(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+) {
+    notify(|observer| observer.try_lock_failed(lock_location, guard_location, guard_kind));
+}
+
+pub(crate) fn guard_released(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    duration: Duration,
+) {
+    notify(|observer| {
+        observer.guard_released(
+            lock_location,
+            guard_location,
+            guard_kind,
+            guard_index,
+            duration,
+        )
+    });
+}
@@ -0,0 +1,122 @@
+use std::sync::{Arc, Mutex};
+
+use opentelemetry::{
+    metrics::{AsyncInstrument, Meter},
+    KeyValue,
+};
+
+use crate::lock_info::{lock_snapshots, GuardInfo, LockInfo};
+
+/// Registers observable instruments which report the data on all the known
+/// guards, as collected by `lock_snapshots`, using the provided `Meter`.
+///
+/// Every data point is described by the following attributes:
+/// - `lock.location`: the location where the lock was created
+/// - `lock.kind`: the `LockKind` of the lock
+/// - `guard.location`: the location where the guard was acquired
+/// - `guard.kind`: the `GuardKind` of the guard
+pub fn register_instruments(meter: &Meter) {
+    // The callbacks are run one after another during each collection, so the
+    // first one takes a snapshot of the locks, which the others then reuse.
+    let snapshot = Arc::new(Mutex::new(Vec::new()));
+
+    let shared = snapshot.clone();
+    meter
+        .u64_observable_counter("locktick.guard.uses")
+        .with_description("The number of times a guard was acquired; estimated if sampled")
+        .with_callback(move |observer| {
+            let locks = lock_snapshots();
+            // Unlike the other statistics, the total is unaffected by resets.
+            observe(observer, &locks, |guard| guard.lifetime_uses as u64);
+            *shared.lock().unwrap() = locks;
+        })
+        .build();
+
+    let shared = snapshot.clone();
+    meter
+        .u64_observable_gauge("locktick.guard.active")
+        .with_description("The number of currently active uses of a guard")
+        .with_callback(move |observer| {
+            let locks = shared.lock().unwrap();
+            observe(observer, &locks, |guard| guard.num_active_uses() as u64);
+        })
+        .build();
+
+    let shared = snapshot.clone();
+    meter
+        .u64_observable_gauge("locktick.guard.waiting")
+        .with_description("The number of tasks currently waiting to acquire a guard")
+        .with_callback(move |observer| {
+            let locks = shared.lock().unwrap();
+            observe(observer, &locks, |guard| guard.num_waiting() as u64);
+        })
+        .build();
+
+    let shared = snapshot.clone();
+    meter
+        .f64_observable_gauge("locktick.guard.wait_time.avg")
+        .with_description("The moving average of the time spent waiting for a guard")
+        .with_unit("s")
+        .with_callback(move |observer| {
+            let locks = shared.lock().unwrap();
+            observe(observer, &locks, |guard| {
+                guard.avg_wait_time().as_secs_f64()
+            });
+        })
+        .build();
+
+    let shared = snapshot.clone();
+    meter
+        .f64_observable_gauge("locktick.guard.wait_time.max")
+        .with_description("The longest time spent waiting for a guard")
+        .with_unit("s")
+        .with_callback(move |observer| {
+            let locks = shared.lock().unwrap();
+            observe(observer, &locks, |guard| guard.max_wait_time.as_secs_f64());
+        })
+        .build();
+
+    let shared = snapshot.clone();
+    meter
+        .f64_observable_gauge("locktick.guard.duration.avg")
+        .with_description("The moving average of the duration of a guard")
+        .with_unit("s")
+        .with_callback(move |observer| {
+            let locks = shared.lock().unwrap();
+            observe(observer, &locks, |guard| guard.avg_duration().as_secs_f64());
+        })
+        .build();
+
+    meter
+        .f64_observable_gauge("locktick.guard.duration.max")
+        .with_description("The longest duration of a guard")
+        .with_unit("s")
+        .with_callback(move |observer| {
+            let locks = snapshot.lock().unwrap();
+            observe(observer, &locks, |guard| guard.max_duration.as_secs_f64());
+        })
+        .build();
+}
+
+// Reports the value obtained from each of the given guards.
+fn observe<T>(
+    observer: &dyn AsyncInstrument<T>,
+    locks: &[LockInfo],
+    value: impl Fn(&GuardInfo) -> T,
+) {
+    for lock in locks {
+        for guard in lock.known_guards.values() {
+            observer.observe(value(guard), &attributes(lock, guard));
+        }
+    }
+}
+
+// Returns the attributes identifying the given guard.
+fn attributes(lock: &LockInfo, guard: &GuardInfo) -> [KeyValue; 4] {
+    [
+        KeyValue::new("lock.location", lock.location.to_string()),
+        KeyValue::new("lock.kind", format!("{:?}", lock.kind)),
+        KeyValue::new("guard.location", guard.location.to_string()),
+        KeyValue::new("guard.kind", format!("{:?}", guard.kind)),
+    ]
+}
@@ -0,0 +1,37 @@
+use std::{io::Write, panic, thread};
+
+use crate::{
+    export::{detailed_text, in_use},
+    lock_info::try_lock_snapshots,
+};
+
+/// Installs a panic hook which, after calling the previously installed one,
+/// prints the data on all the guards that are currently held or waited for,
+/// marking the ones that belong to the panicking thread.
+pub fn install_panic_hook() {
+    let previous_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        previous_hook(info);
+
+        let thread = thread::current();
+        let name = thread.name().unwrap_or("<unnamed>");
+        // The panic could have happened while the lock data was being modified.
+        let report = match try_lock_snapshots() {
+            Some(locks) => {
+                let locks = in_use(&locks);
+                if locks.is_empty() {
+                    "there are no active or awaited guards\n".to_owned()
+                } else {
+                    detailed_text(&locks, Some(thread.id()))
+                }
+            }
+            None => "the lock data is currently unavailable\n".to_owned(),
+        };
+
+        let _ = write!(
+            std::io::stderr().lock(),
+            "locktick: the state of the locks when thread '{name}' panicked:\n{report}"
+        );
+    }));
+}
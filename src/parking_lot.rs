@@ -1,28 +1,43 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use parking_lot::{MutexGuard, RwLockReadGuard, RwLockWriteGuard};
+use parking_lot::{MutexGuard, RwLockReadGuard, RwLockWriteGuard, WaitTimeoutResult};
 #[cfg(feature = "tracing")]
 use tracing::trace;
 
-use crate::lock_info::{
-    call_location, GuardKind, Location, LockGuard, LockInfo, LockKind, WaitGuard,
+use crate::{
+    condvar::CondvarInfo,
+    lock_info::{call_location, LockGuard, LockInfo, WaitGuard},
+    sampling::Sampler,
+    types::{GuardKind, Location, LockKind, LockOptions},
 };
 
 #[derive(Debug)]
 pub struct Mutex<T> {
     lock: parking_lot::Mutex<T>,
     location: Location,
+    sampler: Sampler,
 }
 
 impl<T> Mutex<T> {
     pub fn new(item: T) -> Self {
+        Self::with_options(item, Default::default())
+    }
+
+    /// Creates the lock with the given options, e.g. a name.
+    pub fn with_options(item: T, options: LockOptions) -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Mutex, options);
         Self {
             lock: parking_lot::Mutex::new(item),
-            location: LockInfo::register(LockKind::Mutex),
+            location,
+            sampler,
         }
     }
 
     pub fn lock(&self) -> LockGuard<MutexGuard<'_, T>> {
+        let Some(weight) = self.sampler.sample() else {
+            return LockGuard::untracked(self.lock.lock(), &self.location);
+        };
+
         let guard_kind = GuardKind::Lock;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -32,17 +47,31 @@ impl<T> Mutex<T> {
         let timestamp = Instant::now();
         if let Some(guard) = self.lock.try_lock() {
             let wait_time = timestamp.elapsed();
-            return LockGuard::new(guard, guard_kind, &self.location, guard_location, wait_time);
+            return LockGuard::new(
+                guard,
+                guard_kind,
+                &self.location,
+                guard_location,
+                wait_time,
+                weight,
+            );
         }
 
         // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
+        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location, weight);
         let guard = self.lock.lock();
         let wait_time = timestamp.elapsed();
         LockGuard::from_wait_guard(guard, wait_guard, wait_time)
     }
 
     pub fn try_lock(&self) -> Option<LockGuard<MutexGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .try_lock()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Lock;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -53,6 +82,7 @@ impl<T> Mutex<T> {
         );
         let timestamp = Instant::now();
         let guard = self.lock.try_lock().or_else(|| {
+            crate::observer::try_lock_failed(&self.location, &guard_location, guard_kind);
             #[cfg(feature = "tracing")]
             trace!(
                 "Failed to acquire a {:?} guard at {}",
@@ -68,15 +98,18 @@ impl<T> Mutex<T> {
             &self.location,
             guard_location,
             wait_time,
+            weight,
         ))
     }
 }
 
 impl<T: Default> Default for Mutex<T> {
     fn default() -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Mutex, Default::default());
         Self {
             lock: Default::default(),
-            location: LockInfo::register(LockKind::Mutex),
+            location,
+            sampler,
         }
     }
 }
@@ -85,17 +118,29 @@ impl<T: Default> Default for Mutex<T> {
 pub struct RwLock<T> {
     lock: parking_lot::RwLock<T>,
     location: Location,
+    sampler: Sampler,
 }
 
 impl<T> RwLock<T> {
     pub fn new(item: T) -> Self {
+        Self::with_options(item, Default::default())
+    }
+
+    /// Creates the lock with the given options, e.g. a name.
+    pub fn with_options(item: T, options: LockOptions) -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::RwLock, options);
         Self {
             lock: parking_lot::RwLock::new(item),
-            location: LockInfo::register(LockKind::RwLock),
+            location,
+            sampler,
         }
     }
 
     pub fn read(&self) -> LockGuard<RwLockReadGuard<'_, T>> {
+        let Some(weight) = self.sampler.sample() else {
+            return LockGuard::untracked(self.lock.read(), &self.location);
+        };
+
         let guard_kind = GuardKind::Read;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -105,17 +150,31 @@ impl<T> RwLock<T> {
         let timestamp = Instant::now();
         if let Some(guard) = self.lock.try_read() {
             let wait_time = timestamp.elapsed();
-            return LockGuard::new(guard, guard_kind, &self.location, guard_location, wait_time);
+            return LockGuard::new(
+                guard,
+                guard_kind,
+                &self.location,
+                guard_location,
+                wait_time,
+                weight,
+            );
         }
 
         // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
+        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location, weight);
         let guard = self.lock.read();
         let wait_time = timestamp.elapsed();
         LockGuard::from_wait_guard(guard, wait_guard, wait_time)
     }
 
     pub fn try_read(&self) -> Option<LockGuard<RwLockReadGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .try_read()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Read;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -126,6 +185,7 @@ impl<T> RwLock<T> {
         );
         let timestamp = Instant::now();
         let guard = self.lock.try_read().or_else(|| {
+            crate::observer::try_lock_failed(&self.location, &guard_location, guard_kind);
             #[cfg(feature = "tracing")]
             trace!(
                 "Failed to acquire a {:?} guard at {}",
@@ -141,10 +201,15 @@ impl<T> RwLock<T> {
             &self.location,
             guard_location,
             wait_time,
+            weight,
         ))
     }
 
     pub fn write(&self) -> LockGuard<RwLockWriteGuard<'_, T>> {
+        let Some(weight) = self.sampler.sample() else {
+            return LockGuard::untracked(self.lock.write(), &self.location);
+        };
+
         let guard_kind = GuardKind::Write;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -154,17 +219,31 @@ impl<T> RwLock<T> {
         let timestamp = Instant::now();
         if let Some(guard) = self.lock.try_write() {
             let wait_time = timestamp.elapsed();
-            return LockGuard::new(guard, guard_kind, &self.location, guard_location, wait_time);
+            return LockGuard::new(
+                guard,
+                guard_kind,
+                &self.location,
+                guard_location,
+                wait_time,
+                weight,
+            );
         }
 
         // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
+        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location, weight);
         let guard = self.lock.write();
         let wait_time = timestamp.elapsed();
         LockGuard::from_wait_guard(guard, wait_guard, wait_time)
     }
 
     pub fn try_write(&self) -> Option<LockGuard<RwLockWriteGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .try_write()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Write;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -175,6 +254,7 @@ impl<T> RwLock<T> {
         );
         let timestamp = Instant::now();
         let guard = self.lock.try_write().or_else(|| {
+            crate::observer::try_lock_failed(&self.location, &guard_location, guard_kind);
             #[cfg(feature = "tracing")]
             trace!(
                 "Failed to acquire a {:?} guard at {}",
@@ -190,6 +270,7 @@ impl<T> RwLock<T> {
             &self.location,
             guard_location,
             wait_time,
+            weight,
         ))
     }
 
@@ -200,9 +281,98 @@ impl<T> RwLock<T> {
 
 impl<T: Default> Default for RwLock<T> {
     fn default() -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::RwLock, Default::default());
         Self {
             lock: Default::default(),
-            location: LockInfo::register(LockKind::RwLock),
+            location,
+            sampler,
         }
     }
 }
+
+/// A condition variable that works with the guards of `Mutex`. Waiting on it
+/// releases the guard, and waking up reacquires it as a new use of the guard;
+/// in the meantime, the task is registered as waiting for the guard.
+#[derive(Debug)]
+pub struct Condvar {
+    condvar: parking_lot::Condvar,
+    location: Location,
+}
+
+impl Condvar {
+    pub fn new() -> Self {
+        Self {
+            condvar: parking_lot::Condvar::new(),
+            location: CondvarInfo::register(),
+        }
+    }
+
+    pub fn wait<T>(&self, guard: &mut LockGuard<MutexGuard<'_, T>>) {
+        guard.unlocked(|guard| {
+            let started = CondvarInfo::wait_started(&self.location);
+            self.condvar.wait(guard);
+            let wait_time = started.map_or(Duration::ZERO, |started| {
+                CondvarInfo::wait_finished(&self.location, started, None)
+            });
+            ((), wait_time)
+        })
+    }
+
+    pub fn wait_while<T, F: FnMut(&mut T) -> bool>(
+        &self,
+        guard: &mut LockGuard<MutexGuard<'_, T>>,
+        mut condition: F,
+    ) {
+        while condition(&mut *guard) {
+            self.wait(guard);
+        }
+    }
+
+    pub fn wait_until<T>(
+        &self,
+        guard: &mut LockGuard<MutexGuard<'_, T>>,
+        timeout: Instant,
+    ) -> WaitTimeoutResult {
+        guard.unlocked(|guard| {
+            let started = CondvarInfo::wait_started(&self.location);
+            let result = self.condvar.wait_until(guard, timeout);
+            let wait_time = started.map_or(Duration::ZERO, |started| {
+                let deadline = result.timed_out().then_some(timeout);
+                CondvarInfo::wait_finished(&self.location, started, deadline)
+            });
+            (result, wait_time)
+        })
+    }
+
+    pub fn wait_for<T>(
+        &self,
+        guard: &mut LockGuard<MutexGuard<'_, T>>,
+        timeout: Duration,
+    ) -> WaitTimeoutResult {
+        guard.unlocked(|guard| {
+            let started = CondvarInfo::wait_started(&self.location);
+            let result = self.condvar.wait_for(guard, timeout);
+            let wait_time = started.map_or(Duration::ZERO, |started| {
+                let deadline = result.timed_out().then(|| started + timeout);
+                CondvarInfo::wait_finished(&self.location, started, deadline)
+            });
+            (result, wait_time)
+        })
+    }
+
+    pub fn notify_one(&self) -> bool {
+        CondvarInfo::notified(&self.location, false);
+        self.condvar.notify_one()
+    }
+
+    pub fn notify_all(&self) -> usize {
+        CondvarInfo::notified(&self.location, true);
+        self.condvar.notify_all()
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,69 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::types::{Location, UNTRACKED_LOCATION};
+
+/// A transparent wrapper for the lock guard produced when working with a lock;
+/// with the `passthrough` feature enabled, no accounting is performed, and it
+/// only exists so that the signatures of the wrappers remain unchanged.
+#[repr(transparent)]
+pub struct LockGuard<T>(T);
+
+impl<T> LockGuard<T> {
+    #[inline]
+    pub(crate) fn new(guard: T) -> Self {
+        Self(guard)
+    }
+
+    /// Returns the underlying guard, e.g. in order to use the methods specific
+    /// to it.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    #[inline]
+    #[cfg_attr(not(feature = "parking_lot"), allow(dead_code))]
+    pub(crate) fn as_inner_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    /// Always returns `false`, as no guards are tracked.
+    #[inline]
+    pub fn is_tracked(&self) -> bool {
+        false
+    }
+
+    /// Always returns a placeholder, as the locations aren't recorded.
+    #[inline]
+    pub fn lock_location(&self) -> &Location {
+        &UNTRACKED_LOCATION
+    }
+
+    /// Always returns a placeholder, as the locations aren't recorded.
+    #[inline]
+    pub fn guard_location(&self) -> &Location {
+        &UNTRACKED_LOCATION
+    }
+
+    /// Always returns `usize::MAX`, as the guards aren't indexed.
+    #[inline]
+    pub fn guard_index(&self) -> usize {
+        usize::MAX
+    }
+}
+
+impl<T: Deref> Deref for LockGuard<T> {
+    type Target = T::Target;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.0.deref()
+    }
+}
+
+impl<T: DerefMut> DerefMut for LockGuard<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.deref_mut()
+    }
+}
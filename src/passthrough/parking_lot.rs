@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use parking_lot::{MutexGuard, RwLockReadGuard, RwLockWriteGuard, WaitTimeoutResult};
+
+use crate::{passthrough::LockGuard, types::LockOptions};
+
+#[derive(Debug, Default)]
+pub struct Mutex<T>(parking_lot::Mutex<T>);
+
+impl<T> Mutex<T> {
+    #[inline]
+    pub fn new(item: T) -> Self {
+        Self(parking_lot::Mutex::new(item))
+    }
+
+    #[inline]
+    pub fn with_options(item: T, _options: LockOptions) -> Self {
+        Self::new(item)
+    }
+
+    #[inline]
+    pub fn lock(&self) -> LockGuard<MutexGuard<'_, T>> {
+        LockGuard::new(self.0.lock())
+    }
+
+    #[inline]
+    pub fn try_lock(&self) -> Option<LockGuard<MutexGuard<'_, T>>> {
+        self.0.try_lock().map(LockGuard::new)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RwLock<T>(parking_lot::RwLock<T>);
+
+impl<T> RwLock<T> {
+    #[inline]
+    pub fn new(item: T) -> Self {
+        Self(parking_lot::RwLock::new(item))
+    }
+
+    #[inline]
+    pub fn with_options(item: T, _options: LockOptions) -> Self {
+        Self::new(item)
+    }
+
+    #[inline]
+    pub fn read(&self) -> LockGuard<RwLockReadGuard<'_, T>> {
+        LockGuard::new(self.0.read())
+    }
+
+    #[inline]
+    pub fn try_read(&self) -> Option<LockGuard<RwLockReadGuard<'_, T>>> {
+        self.0.try_read().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn write(&self) -> LockGuard<RwLockWriteGuard<'_, T>> {
+        LockGuard::new(self.0.write())
+    }
+
+    #[inline]
+    pub fn try_write(&self) -> Option<LockGuard<RwLockWriteGuard<'_, T>>> {
+        self.0.try_write().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Condvar(parking_lot::Condvar);
+
+impl Condvar {
+    #[inline]
+    pub fn new() -> Self {
+        Self(parking_lot::Condvar::new())
+    }
+
+    #[inline]
+    pub fn wait<T>(&self, guard: &mut LockGuard<MutexGuard<'_, T>>) {
+        self.0.wait(guard.as_inner_mut())
+    }
+
+    #[inline]
+    pub fn wait_while<T, F: FnMut(&mut T) -> bool>(
+        &self,
+        guard: &mut LockGuard<MutexGuard<'_, T>>,
+        condition: F,
+    ) {
+        self.0.wait_while(guard.as_inner_mut(), condition)
+    }
+
+    #[inline]
+    pub fn wait_until<T>(
+        &self,
+        guard: &mut LockGuard<MutexGuard<'_, T>>,
+        timeout: Instant,
+    ) -> WaitTimeoutResult {
+        self.0.wait_until(guard.as_inner_mut(), timeout)
+    }
+
+    #[inline]
+    pub fn wait_for<T>(
+        &self,
+        guard: &mut LockGuard<MutexGuard<'_, T>>,
+        timeout: Duration,
+    ) -> WaitTimeoutResult {
+        self.0.wait_for(guard.as_inner_mut(), timeout)
+    }
+
+    #[inline]
+    pub fn notify_one(&self) -> bool {
+        self.0.notify_one()
+    }
+
+    #[inline]
+    pub fn notify_all(&self) -> usize {
+        self.0.notify_all()
+    }
+}
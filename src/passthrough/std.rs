@@ -0,0 +1,148 @@
+use std::{
+    sync::{
+        MutexGuard, PoisonError, RwLockReadGuard, RwLockWriteGuard, TryLockError, WaitTimeoutResult,
+    },
+    time::Duration,
+};
+
+use crate::{passthrough::LockGuard, types::LockOptions};
+
+#[derive(Debug, Default)]
+pub struct Mutex<T>(std::sync::Mutex<T>);
+
+impl<T> Mutex<T> {
+    #[inline]
+    pub fn new(item: T) -> Self {
+        Self(std::sync::Mutex::new(item))
+    }
+
+    #[inline]
+    pub fn with_options(item: T, _options: LockOptions) -> Self {
+        Self::new(item)
+    }
+
+    #[inline]
+    pub fn lock(&self) -> Result<LockGuard<MutexGuard<'_, T>>, PoisonError<MutexGuard<'_, T>>> {
+        self.0.lock().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn try_lock(
+        &self,
+    ) -> Result<LockGuard<MutexGuard<'_, T>>, TryLockError<MutexGuard<'_, T>>> {
+        self.0.try_lock().map(LockGuard::new)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RwLock<T>(std::sync::RwLock<T>);
+
+impl<T> RwLock<T> {
+    #[inline]
+    pub fn new(item: T) -> Self {
+        Self(std::sync::RwLock::new(item))
+    }
+
+    #[inline]
+    pub fn with_options(item: T, _options: LockOptions) -> Self {
+        Self::new(item)
+    }
+
+    #[inline]
+    pub fn read(
+        &self,
+    ) -> Result<LockGuard<RwLockReadGuard<'_, T>>, PoisonError<RwLockReadGuard<'_, T>>> {
+        self.0.read().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn try_read(
+        &self,
+    ) -> Result<LockGuard<RwLockReadGuard<'_, T>>, TryLockError<RwLockReadGuard<'_, T>>> {
+        self.0.try_read().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn write(
+        &self,
+    ) -> Result<LockGuard<RwLockWriteGuard<'_, T>>, PoisonError<RwLockWriteGuard<'_, T>>> {
+        self.0.write().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn try_write(
+        &self,
+    ) -> Result<LockGuard<RwLockWriteGuard<'_, T>>, TryLockError<RwLockWriteGuard<'_, T>>> {
+        self.0.try_write().map(LockGuard::new)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Condvar(std::sync::Condvar);
+
+impl Condvar {
+    #[inline]
+    pub fn new() -> Self {
+        Self(std::sync::Condvar::new())
+    }
+
+    #[inline]
+    pub fn wait<'a, T>(
+        &self,
+        guard: LockGuard<MutexGuard<'a, T>>,
+    ) -> Result<LockGuard<MutexGuard<'a, T>>, PoisonError<MutexGuard<'a, T>>> {
+        self.0.wait(guard.into_inner()).map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn wait_while<'a, T, F: FnMut(&mut T) -> bool>(
+        &self,
+        guard: LockGuard<MutexGuard<'a, T>>,
+        condition: F,
+    ) -> Result<LockGuard<MutexGuard<'a, T>>, PoisonError<MutexGuard<'a, T>>> {
+        self.0
+            .wait_while(guard.into_inner(), condition)
+            .map(LockGuard::new)
+    }
+
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: LockGuard<MutexGuard<'a, T>>,
+        timeout: Duration,
+    ) -> Result<
+        (LockGuard<MutexGuard<'a, T>>, WaitTimeoutResult),
+        PoisonError<(MutexGuard<'a, T>, WaitTimeoutResult)>,
+    > {
+        self.0
+            .wait_timeout(guard.into_inner(), timeout)
+            .map(|(guard, result)| (LockGuard::new(guard), result))
+    }
+
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn wait_timeout_while<'a, T, F: FnMut(&mut T) -> bool>(
+        &self,
+        guard: LockGuard<MutexGuard<'a, T>>,
+        timeout: Duration,
+        condition: F,
+    ) -> Result<
+        (LockGuard<MutexGuard<'a, T>>, WaitTimeoutResult),
+        PoisonError<(MutexGuard<'a, T>, WaitTimeoutResult)>,
+    > {
+        self.0
+            .wait_timeout_while(guard.into_inner(), timeout, condition)
+            .map(|(guard, result)| (LockGuard::new(guard), result))
+    }
+
+    #[inline]
+    pub fn notify_one(&self) {
+        self.0.notify_one();
+    }
+
+    #[inline]
+    pub fn notify_all(&self) {
+        self.0.notify_all();
+    }
+}
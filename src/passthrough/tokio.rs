@@ -0,0 +1,506 @@
+use std::{
+    mem,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+pub use tokio::sync::futures::Notified;
+use tokio::sync::{
+    AcquireError, MappedMutexGuard, MutexGuard, OwnedSemaphorePermit, RwLockMappedWriteGuard,
+    RwLockReadGuard, RwLockWriteGuard, SemaphorePermit, TryAcquireError, TryLockError,
+};
+
+use crate::{passthrough::LockGuard, types::LockOptions};
+
+#[derive(Debug, Default)]
+pub struct Mutex<T>(tokio::sync::Mutex<T>);
+
+impl<T> Mutex<T> {
+    #[inline]
+    pub fn new(item: T) -> Self {
+        Self(tokio::sync::Mutex::new(item))
+    }
+
+    #[inline]
+    pub fn with_options(item: T, _options: LockOptions) -> Self {
+        Self::new(item)
+    }
+
+    #[inline]
+    pub async fn lock(&self) -> LockGuard<MutexGuard<'_, T>> {
+        LockGuard::new(self.0.lock().await)
+    }
+
+    #[inline]
+    pub fn try_lock(&self) -> Result<LockGuard<MutexGuard<'_, T>>, TryLockError> {
+        self.0.try_lock().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub async fn lock_owned(self: Arc<Self>) -> LockGuard<OwnedMutexGuard<T>>
+    where
+        T: 'static,
+    {
+        let guard = self.0.lock().await;
+        // SAFETY: the guard is borrowed from the lock in `self`.
+        LockGuard::new(unsafe { OwnedMutexGuard::new(guard, self.clone()) })
+    }
+
+    #[inline]
+    pub fn try_lock_owned(self: Arc<Self>) -> Result<LockGuard<OwnedMutexGuard<T>>, TryLockError>
+    where
+        T: 'static,
+    {
+        let guard = self.0.try_lock()?;
+        // SAFETY: the guard is borrowed from the lock in `self`.
+        Ok(LockGuard::new(unsafe {
+            OwnedMutexGuard::new(guard, self.clone())
+        }))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RwLock<T>(tokio::sync::RwLock<T>);
+
+impl<T> RwLock<T> {
+    #[inline]
+    pub fn new(item: T) -> Self {
+        Self(tokio::sync::RwLock::new(item))
+    }
+
+    #[inline]
+    pub fn with_options(item: T, _options: LockOptions) -> Self {
+        Self::new(item)
+    }
+
+    #[inline]
+    pub async fn read(&self) -> LockGuard<RwLockReadGuard<'_, T>> {
+        LockGuard::new(self.0.read().await)
+    }
+
+    #[inline]
+    pub fn try_read(&self) -> Result<LockGuard<RwLockReadGuard<'_, T>>, TryLockError> {
+        self.0.try_read().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub async fn write(&self) -> LockGuard<RwLockWriteGuard<'_, T>> {
+        LockGuard::new(self.0.write().await)
+    }
+
+    #[inline]
+    pub fn try_write(&self) -> Result<LockGuard<RwLockWriteGuard<'_, T>>, TryLockError> {
+        self.0.try_write().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub async fn read_owned(self: Arc<Self>) -> LockGuard<OwnedRwLockReadGuard<T>>
+    where
+        T: 'static,
+    {
+        let guard = self.0.read().await;
+        // SAFETY: the guard is borrowed from the lock in `self`.
+        LockGuard::new(unsafe { OwnedRwLockReadGuard::new(guard, self.clone()) })
+    }
+
+    #[inline]
+    pub fn try_read_owned(
+        self: Arc<Self>,
+    ) -> Result<LockGuard<OwnedRwLockReadGuard<T>>, TryLockError>
+    where
+        T: 'static,
+    {
+        let guard = self.0.try_read()?;
+        // SAFETY: the guard is borrowed from the lock in `self`.
+        Ok(LockGuard::new(unsafe {
+            OwnedRwLockReadGuard::new(guard, self.clone())
+        }))
+    }
+
+    #[inline]
+    pub async fn write_owned(self: Arc<Self>) -> LockGuard<OwnedRwLockWriteGuard<T>>
+    where
+        T: 'static,
+    {
+        let guard = self.0.write().await;
+        // SAFETY: the guard is borrowed from the lock in `self`.
+        LockGuard::new(unsafe { OwnedRwLockWriteGuard::new(guard, self.clone()) })
+    }
+
+    #[inline]
+    pub fn try_write_owned(
+        self: Arc<Self>,
+    ) -> Result<LockGuard<OwnedRwLockWriteGuard<T>>, TryLockError>
+    where
+        T: 'static,
+    {
+        let guard = self.0.try_write()?;
+        // SAFETY: the guard is borrowed from the lock in `self`.
+        Ok(LockGuard::new(unsafe {
+            OwnedRwLockWriteGuard::new(guard, self.clone())
+        }))
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+// The owned guards work like the tracked ones, i.e. they store guards whose
+// borrows of the locks are extended, along with the `Arc`s that own the locks.
+
+/// An owned guard of a `Mutex`, which keeps the lock alive.
+#[derive(Debug)]
+pub struct OwnedMutexGuard<T: 'static> {
+    guard: MutexGuard<'static, T>,
+    lock: Arc<Mutex<T>>,
+}
+
+impl<T> OwnedMutexGuard<T> {
+    // SAFETY: the guard must be borrowed from the given lock.
+    #[inline]
+    unsafe fn new(guard: MutexGuard<'_, T>, lock: Arc<Mutex<T>>) -> Self {
+        Self {
+            guard: unsafe { mem::transmute::<MutexGuard<'_, T>, MutexGuard<'static, T>>(guard) },
+            lock,
+        }
+    }
+
+    /// Returns the `Mutex` that the guard was acquired from.
+    #[inline]
+    pub fn mutex(this: &Self) -> &Arc<Mutex<T>> {
+        &this.lock
+    }
+
+    /// Makes a guard for a component of the locked data.
+    #[inline]
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> OwnedMappedMutexGuard<T, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        OwnedMappedMutexGuard {
+            guard: MutexGuard::map(this.guard, f),
+            lock: this.lock,
+        }
+    }
+}
+
+impl<T> Deref for OwnedMutexGuard<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for OwnedMutexGuard<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+/// An owned guard of a component of the data locked by a `Mutex`, produced by
+/// `OwnedMutexGuard::map`.
+#[derive(Debug)]
+pub struct OwnedMappedMutexGuard<T: 'static, U: ?Sized + 'static> {
+    guard: MappedMutexGuard<'static, U>,
+    lock: Arc<Mutex<T>>,
+}
+
+impl<T, U: ?Sized> OwnedMappedMutexGuard<T, U> {
+    /// Returns the `Mutex` that the guard was acquired from.
+    #[inline]
+    pub fn mutex(this: &Self) -> &Arc<Mutex<T>> {
+        &this.lock
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedMappedMutexGuard<T, U> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &U {
+        &self.guard
+    }
+}
+
+impl<T, U: ?Sized> DerefMut for OwnedMappedMutexGuard<T, U> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut U {
+        &mut self.guard
+    }
+}
+
+/// An owned read guard of a `RwLock`, which keeps the lock alive; the data it
+/// points to can be narrowed down to a component via `OwnedRwLockReadGuard::map`.
+#[derive(Debug)]
+pub struct OwnedRwLockReadGuard<T: 'static, U: ?Sized + 'static = T> {
+    guard: RwLockReadGuard<'static, U>,
+    lock: Arc<RwLock<T>>,
+}
+
+impl<T> OwnedRwLockReadGuard<T> {
+    // SAFETY: the guard must be borrowed from the given lock.
+    #[inline]
+    unsafe fn new(guard: RwLockReadGuard<'_, T>, lock: Arc<RwLock<T>>) -> Self {
+        Self {
+            guard: unsafe {
+                mem::transmute::<RwLockReadGuard<'_, T>, RwLockReadGuard<'static, T>>(guard)
+            },
+            lock,
+        }
+    }
+}
+
+impl<T, U: ?Sized> OwnedRwLockReadGuard<T, U> {
+    /// Returns the `RwLock` that the guard was acquired from.
+    #[inline]
+    pub fn rwlock(this: &Self) -> &Arc<RwLock<T>> {
+        &this.lock
+    }
+
+    /// Makes a guard for a component of the locked data.
+    #[inline]
+    pub fn map<V: ?Sized, F>(this: Self, f: F) -> OwnedRwLockReadGuard<T, V>
+    where
+        F: FnOnce(&U) -> &V,
+    {
+        OwnedRwLockReadGuard {
+            guard: RwLockReadGuard::map(this.guard, f),
+            lock: this.lock,
+        }
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedRwLockReadGuard<T, U> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &U {
+        &self.guard
+    }
+}
+
+/// An owned write guard of a `RwLock`, which keeps the lock alive.
+#[derive(Debug)]
+pub struct OwnedRwLockWriteGuard<T: 'static> {
+    guard: RwLockWriteGuard<'static, T>,
+    lock: Arc<RwLock<T>>,
+}
+
+impl<T> OwnedRwLockWriteGuard<T> {
+    // SAFETY: the guard must be borrowed from the given lock.
+    #[inline]
+    unsafe fn new(guard: RwLockWriteGuard<'_, T>, lock: Arc<RwLock<T>>) -> Self {
+        Self {
+            guard: unsafe {
+                mem::transmute::<RwLockWriteGuard<'_, T>, RwLockWriteGuard<'static, T>>(guard)
+            },
+            lock,
+        }
+    }
+
+    /// Returns the `RwLock` that the guard was acquired from.
+    #[inline]
+    pub fn rwlock(this: &Self) -> &Arc<RwLock<T>> {
+        &this.lock
+    }
+
+    /// Makes a guard for a component of the locked data.
+    #[inline]
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> OwnedRwLockMappedWriteGuard<T, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        OwnedRwLockMappedWriteGuard {
+            guard: RwLockWriteGuard::map(this.guard, f),
+            lock: this.lock,
+        }
+    }
+
+    /// Atomically turns the guard into a read guard, without allowing any
+    /// writers to acquire the lock in the meantime.
+    #[inline]
+    pub fn downgrade(self) -> OwnedRwLockReadGuard<T> {
+        OwnedRwLockReadGuard {
+            guard: self.guard.downgrade(),
+            lock: self.lock,
+        }
+    }
+}
+
+impl<T> Deref for OwnedRwLockWriteGuard<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for OwnedRwLockWriteGuard<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+/// An owned write guard of a component of the data locked by a `RwLock`,
+/// produced by `OwnedRwLockWriteGuard::map`.
+#[derive(Debug)]
+pub struct OwnedRwLockMappedWriteGuard<T: 'static, U: ?Sized + 'static> {
+    guard: RwLockMappedWriteGuard<'static, U>,
+    lock: Arc<RwLock<T>>,
+}
+
+impl<T, U: ?Sized> OwnedRwLockMappedWriteGuard<T, U> {
+    /// Returns the `RwLock` that the guard was acquired from.
+    #[inline]
+    pub fn rwlock(this: &Self) -> &Arc<RwLock<T>> {
+        &this.lock
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedRwLockMappedWriteGuard<T, U> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &U {
+        &self.guard
+    }
+}
+
+impl<T, U: ?Sized> DerefMut for OwnedRwLockMappedWriteGuard<T, U> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut U {
+        &mut self.guard
+    }
+}
+
+#[derive(Debug)]
+pub struct Semaphore(Arc<tokio::sync::Semaphore>);
+
+impl Semaphore {
+    #[inline]
+    pub fn new(permits: usize) -> Self {
+        Self(Arc::new(tokio::sync::Semaphore::new(permits)))
+    }
+
+    #[inline]
+    pub fn with_options(permits: usize, _options: LockOptions) -> Self {
+        Self::new(permits)
+    }
+
+    #[inline]
+    pub fn available_permits(&self) -> usize {
+        self.0.available_permits()
+    }
+
+    #[inline]
+    pub fn add_permits(&self, n: usize) {
+        self.0.add_permits(n);
+    }
+
+    #[inline]
+    pub fn close(&self) {
+        self.0.close();
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    #[inline]
+    pub async fn acquire(&self) -> Result<LockGuard<SemaphorePermit<'_>>, AcquireError> {
+        self.0.acquire().await.map(LockGuard::new)
+    }
+
+    #[inline]
+    pub async fn acquire_many(
+        &self,
+        n: u32,
+    ) -> Result<LockGuard<SemaphorePermit<'_>>, AcquireError> {
+        self.0.acquire_many(n).await.map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn try_acquire(&self) -> Result<LockGuard<SemaphorePermit<'_>>, TryAcquireError> {
+        self.0.try_acquire().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn try_acquire_many(
+        &self,
+        n: u32,
+    ) -> Result<LockGuard<SemaphorePermit<'_>>, TryAcquireError> {
+        self.0.try_acquire_many(n).map(LockGuard::new)
+    }
+
+    #[inline]
+    pub async fn acquire_owned(
+        self: Arc<Self>,
+    ) -> Result<LockGuard<OwnedSemaphorePermit>, AcquireError> {
+        self.0.clone().acquire_owned().await.map(LockGuard::new)
+    }
+
+    #[inline]
+    pub async fn acquire_many_owned(
+        self: Arc<Self>,
+        n: u32,
+    ) -> Result<LockGuard<OwnedSemaphorePermit>, AcquireError> {
+        self.0
+            .clone()
+            .acquire_many_owned(n)
+            .await
+            .map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn try_acquire_owned(
+        self: Arc<Self>,
+    ) -> Result<LockGuard<OwnedSemaphorePermit>, TryAcquireError> {
+        self.0.clone().try_acquire_owned().map(LockGuard::new)
+    }
+
+    #[inline]
+    pub fn try_acquire_many_owned(
+        self: Arc<Self>,
+        n: u32,
+    ) -> Result<LockGuard<OwnedSemaphorePermit>, TryAcquireError> {
+        self.0.clone().try_acquire_many_owned(n).map(LockGuard::new)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Notify(tokio::sync::Notify);
+
+impl Notify {
+    #[inline]
+    pub fn new() -> Self {
+        Self(tokio::sync::Notify::new())
+    }
+
+    #[inline]
+    pub fn with_options(_options: LockOptions) -> Self {
+        Self::new()
+    }
+
+    #[inline]
+    pub fn notified(&self) -> Notified<'_> {
+        self.0.notified()
+    }
+
+    #[inline]
+    pub fn notify_one(&self) {
+        self.0.notify_one();
+    }
+
+    #[inline]
+    pub fn notify_waiters(&self) {
+        self.0.notify_waiters();
+    }
+}
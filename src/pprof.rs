@@ -0,0 +1,206 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    time::SystemTime,
+};
+
+use prost::Message;
+
+use crate::stacks::{captured_stacks, Frame};
+
+/// Writes a contention profile based on all the captured stacks to the given writer,
+/// using the (uncompressed) protobuf format understood by `pprof`. Like the mutex
+/// profile in Go, it contains the number of contentions and the total delay for
+/// each of the call stacks that had to wait to acquire a guard.
+pub fn write_pprof<W: Write>(mut writer: W) -> io::Result<()> {
+    let mut builder = ProfileBuilder::default();
+
+    let sample_type = vec![
+        builder.value_type("contentions", "count"),
+        builder.value_type("delay", "nanoseconds"),
+    ];
+    let period_type = Some(builder.value_type("contentions", "count"));
+
+    let mut sample = Vec::new();
+    for (frames, stats) in captured_stacks() {
+        if stats.num_waits == 0 {
+            continue;
+        }
+
+        sample.push(Sample {
+            location_id: frames
+                .iter()
+                .map(|frame| builder.location_id(frame))
+                .collect(),
+            value: vec![stats.num_waits as i64, stats.wait_time.as_nanos() as i64],
+        });
+    }
+
+    let time_nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as i64;
+
+    let profile = Profile {
+        sample_type,
+        sample,
+        location: builder.locations,
+        function: builder.functions,
+        string_table: builder.strings,
+        time_nanos,
+        period_type,
+        period: 1,
+    };
+
+    writer.write_all(&profile.encode_to_vec())
+}
+
+// Deduplicates the strings, functions and locations referenced by the profile.
+struct ProfileBuilder {
+    strings: Vec<String>,
+    string_ids: HashMap<String, i64>,
+    functions: Vec<Function>,
+    function_ids: HashMap<(i64, i64), u64>,
+    locations: Vec<Location>,
+    location_ids: HashMap<(usize, u64, i64), u64>,
+}
+
+impl Default for ProfileBuilder {
+    fn default() -> Self {
+        // The first entry in the string table must be an empty string.
+        Self {
+            strings: vec![String::new()],
+            string_ids: [(String::new(), 0)].into(),
+            functions: Default::default(),
+            function_ids: Default::default(),
+            locations: Default::default(),
+            location_ids: Default::default(),
+        }
+    }
+}
+
+impl ProfileBuilder {
+    fn string_id(&mut self, s: &str) -> i64 {
+        if let Some(id) = self.string_ids.get(s) {
+            return *id;
+        }
+
+        let id = self.strings.len() as i64;
+        self.strings.push(s.to_owned());
+        self.string_ids.insert(s.to_owned(), id);
+        id
+    }
+
+    fn value_type(&mut self, r#type: &str, unit: &str) -> ValueType {
+        ValueType {
+            r#type: self.string_id(r#type),
+            unit: self.string_id(unit),
+        }
+    }
+
+    fn location_id(&mut self, frame: &Frame) -> u64 {
+        let name = self.string_id(&frame.name);
+        let filename = frame
+            .file
+            .as_ref()
+            .map(|file| self.string_id(&file.to_string_lossy()))
+            .unwrap_or_default();
+
+        let function_id = *self
+            .function_ids
+            .entry((name, filename))
+            .or_insert_with(|| {
+                let id = self.functions.len() as u64 + 1;
+                self.functions.push(Function {
+                    id,
+                    name,
+                    system_name: name,
+                    filename,
+                });
+                id
+            });
+
+        let line = frame.line.unwrap_or_default() as i64;
+        *self
+            .location_ids
+            .entry((frame.address, function_id, line))
+            .or_insert_with(|| {
+                let id = self.locations.len() as u64 + 1;
+                self.locations.push(Location {
+                    id,
+                    address: frame.address as u64,
+                    line: vec![Line { function_id, line }],
+                });
+                id
+            })
+    }
+}
+
+// The following are the subsets of the messages defined in pprof's profile.proto
+// that are needed in order to produce a contention profile.
+
+#[derive(Clone, PartialEq, Message)]
+struct Profile {
+    #[prost(message, repeated, tag = "1")]
+    sample_type: Vec<ValueType>,
+    #[prost(message, repeated, tag = "2")]
+    sample: Vec<Sample>,
+    #[prost(message, repeated, tag = "4")]
+    location: Vec<Location>,
+    #[prost(message, repeated, tag = "5")]
+    function: Vec<Function>,
+    #[prost(string, repeated, tag = "6")]
+    string_table: Vec<String>,
+    #[prost(int64, tag = "9")]
+    time_nanos: i64,
+    #[prost(message, optional, tag = "11")]
+    period_type: Option<ValueType>,
+    #[prost(int64, tag = "12")]
+    period: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ValueType {
+    #[prost(int64, tag = "1")]
+    r#type: i64,
+    #[prost(int64, tag = "2")]
+    unit: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Sample {
+    #[prost(uint64, repeated, tag = "1")]
+    location_id: Vec<u64>,
+    #[prost(int64, repeated, tag = "2")]
+    value: Vec<i64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Location {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(uint64, tag = "3")]
+    address: u64,
+    #[prost(message, repeated, tag = "4")]
+    line: Vec<Line>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Line {
+    #[prost(uint64, tag = "1")]
+    function_id: u64,
+    #[prost(int64, tag = "2")]
+    line: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Function {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(int64, tag = "2")]
+    name: i64,
+    #[prost(int64, tag = "3")]
+    system_name: i64,
+    #[prost(int64, tag = "4")]
+    filename: i64,
+}
@@ -0,0 +1,36 @@
+use std::{io::Write, sync::Once};
+
+use crate::{export::to_summary, lock_info::try_lock_snapshots};
+
+// The number of entries in each of the rankings included in the report.
+const REPORT_LIMIT: usize = 10;
+
+/// Registers a handler that prints a summary of the data related to all the
+/// locks to the standard error output when the process exits normally, i.e.
+/// when `main` returns or `std::process::exit` is called. It ranks the most
+/// contended locks and the longest guard holds, and lists the guards that
+/// are still in use; calling it more than once has no additional effect.
+pub fn report_on_exit() {
+    static REGISTERED: Once = Once::new();
+
+    REGISTERED.call_once(|| {
+        // SAFETY: the handler is a plain function that catches any panics.
+        unsafe {
+            libc::atexit(print_report);
+        }
+    });
+}
+
+extern "C" fn print_report() {
+    // Unwinding out of an `extern "C"` function is undefined behavior, and
+    // there is nothing left to do about a panic this late anyway.
+    let _ = std::panic::catch_unwind(|| {
+        // Other threads could still be running at this point, so don't block.
+        let report = match try_lock_snapshots() {
+            Some(locks) => to_summary(&locks, REPORT_LIMIT),
+            None => "locktick: the lock data is currently unavailable\n".to_owned(),
+        };
+
+        let _ = std::io::stderr().lock().write_all(report.as_bytes());
+    });
+}
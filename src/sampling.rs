@@ -0,0 +1,132 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        LazyLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::lock_info::is_enabled;
+
+// The currently applicable sampling mode in the highest bits, and its
+// parameter in the remaining ones; they are packed together, so that they are
+// always read consistently.
+static SAMPLING: AtomicU64 = AtomicU64::new(MODE_ALL);
+
+const MODE_ALL: u64 = 0;
+const MODE_ONE_IN: u64 = 1 << 62;
+const MODE_INTERVAL: u64 = 2 << 62;
+const MODE_MASK: u64 = 3 << 62;
+const PARAM_MASK: u64 = !MODE_MASK;
+
+// The point in time that the sampling timestamps are relative to.
+static EPOCH: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Determines which guard acquisitions are tracked; the ones that aren't
+/// sampled bypass the accounting. The number of uses of a guard that was
+/// sampled is then an estimate, available via `GuardInfo::estimated_uses`,
+/// while the other statistics are based on the sampled uses alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+    /// All the acquisitions are tracked; this is the default.
+    All,
+    /// Only 1 in N acquisitions of each lock is tracked.
+    OneIn(u64),
+    /// At most a single acquisition of each lock within the given interval
+    /// is tracked.
+    Interval(Duration),
+}
+
+/// Sets the sampling mode, which applies to all the subsequent acquisitions.
+/// `Sampling::OneIn(0)` and `Sampling::OneIn(1)` are equivalent to `Sampling::All`.
+pub fn set_sampling(sampling: Sampling) {
+    let (mode, param) = match sampling {
+        Sampling::All | Sampling::OneIn(0 | 1) => (MODE_ALL, 0),
+        Sampling::OneIn(n) => (MODE_ONE_IN, n.min(PARAM_MASK)),
+        Sampling::Interval(interval) => (
+            MODE_INTERVAL,
+            interval.as_nanos().min(PARAM_MASK as u128) as u64,
+        ),
+    };
+    SAMPLING.store(mode | param, Ordering::Relaxed);
+}
+
+/// Returns the current sampling mode.
+pub fn sampling() -> Sampling {
+    let sampling = SAMPLING.load(Ordering::Relaxed);
+    let param = sampling & PARAM_MASK;
+    match sampling & MODE_MASK {
+        MODE_ONE_IN => Sampling::OneIn(param),
+        MODE_INTERVAL => Sampling::Interval(Duration::from_nanos(param)),
+        _ => Sampling::All,
+    }
+}
+
+/// Decides which acquisitions of a single lock are tracked.
+#[derive(Debug)]
+pub(crate) struct Sampler {
+    // Indicates whether the lock is excluded from the accounting altogether.
+    excluded: bool,
+    // The number of acquisitions since the last sampled one.
+    skipped: AtomicUsize,
+    // The time of the last sampled acquisition, relative to the `EPOCH`.
+    last_sample: AtomicU64,
+}
+
+impl Sampler {
+    pub(crate) fn new(excluded: bool) -> Self {
+        Self {
+            excluded,
+            skipped: Default::default(),
+            last_sample: Default::default(),
+        }
+    }
+
+    /// Returns the number of acquisitions represented by the current one if
+    /// it is to be tracked, or `None` if it should bypass the accounting.
+    pub(crate) fn sample(&self) -> Option<usize> {
+        if self.excluded || !is_enabled() {
+            return None;
+        }
+
+        let sampling = SAMPLING.load(Ordering::Relaxed);
+        let param = sampling & PARAM_MASK;
+        match sampling & MODE_MASK {
+            MODE_ONE_IN => {
+                let n = param as usize;
+                if n <= 1 {
+                    return Some(1);
+                }
+                let skipped = self.skipped.fetch_add(1, Ordering::Relaxed) + 1;
+                if skipped % n == 0 {
+                    Some(n)
+                } else {
+                    None
+                }
+            }
+            MODE_INTERVAL => {
+                let interval = param;
+                let now = EPOCH.elapsed().as_nanos() as u64;
+                let last_sample = self.last_sample.load(Ordering::Relaxed);
+                // The first acquisition is always sampled.
+                if (last_sample == 0 || now.saturating_sub(last_sample) >= interval)
+                    && self
+                        .last_sample
+                        .compare_exchange(
+                            last_sample,
+                            now.max(1),
+                            Ordering::Relaxed,
+                            Ordering::Relaxed,
+                        )
+                        .is_ok()
+                {
+                    Some(self.skipped.swap(0, Ordering::Relaxed) + 1)
+                } else {
+                    self.skipped.fetch_add(1, Ordering::Relaxed);
+                    None
+                }
+            }
+            _ => Some(1),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::PathBuf,
+    thread,
+    time::SystemTime,
+};
+
+pub use signal_hook::consts::{SIGUSR1, SIGUSR2};
+use signal_hook::iterator::Signals;
+
+use crate::{export::to_detailed_text, lock_info::lock_snapshots};
+
+/// The destination of the dumps produced by `install_dump_handler`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DumpTarget {
+    /// The standard error output.
+    Stderr,
+    /// A file that each dump is appended to; it is created if it doesn't exist.
+    File(PathBuf),
+}
+
+/// Installs a handler which, whenever the process receives the given signal
+/// (e.g. `SIGUSR1`), dumps the data on all the locks to the given target,
+/// including the current holders and waiters of each guard. The signal
+/// handler itself only wakes up a dedicated thread that produces the dump.
+pub fn install_dump_handler(signal: i32, target: DumpTarget) -> io::Result<()> {
+    let mut signals = Signals::new([signal])?;
+
+    thread::Builder::new()
+        .name("locktick-signal".into())
+        .spawn(move || {
+            for signal in signals.forever() {
+                // There is nothing meaningful to be done about a failed dump.
+                let _ = dump(signal, &target);
+            }
+        })?;
+
+    Ok(())
+}
+
+// Writes the data on all the locks to the given target.
+fn dump(signal: i32, target: &DumpTarget) -> io::Result<()> {
+    let locks = lock_snapshots();
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let dump = format!(
+        "locktick dump (signal {signal}, {} locks, at {:.3}s since the epoch):\n{}",
+        locks.len(),
+        timestamp.as_secs_f64(),
+        to_detailed_text(&locks),
+    );
+
+    match target {
+        DumpTarget::Stderr => io::stderr().lock().write_all(dump.as_bytes()),
+        DumpTarget::File(path) => OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(dump.as_bytes()),
+    }
+}
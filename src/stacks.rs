@@ -0,0 +1,194 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, LazyLock, Mutex,
+    },
+    time::Duration,
+};
+
+use backtrace::Backtrace;
+
+// Indicates whether the stacks are currently being captured.
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+// Contains all the captured stacks, keyed by the instruction pointers of their frames.
+static STACKS: LazyLock<Mutex<HashMap<Vec<usize>, Arc<StackEntry>>>> =
+    LazyLock::new(Default::default);
+
+/// Starts capturing the call stacks of the users of all the guards that have
+/// to be waited for, discarding any stacks that might have been captured
+/// previously; the acquisitions that don't involve waiting aren't captured,
+/// as it would be too expensive.
+pub fn start_capture() {
+    STACKS.lock().unwrap().clear();
+    CAPTURING.store(true, Ordering::Release);
+}
+
+/// Stops capturing the call stacks; the ones that were already captured are
+/// retained until the capture is started again.
+pub fn stop_capture() {
+    CAPTURING.store(false, Ordering::Release);
+}
+
+/// The value used to weigh the captured stacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackWeight {
+    /// The total time spent waiting to acquire guards, in nanoseconds.
+    WaitTime,
+    /// The total time spent holding guards that had to be waited for, in
+    /// nanoseconds.
+    HoldTime,
+}
+
+/// Writes all the captured stacks to the given writer in the collapsed (folded)
+/// format, which can be used as input for `inferno` or `flamegraph.pl`.
+pub fn write_folded<W: Write>(mut writer: W, weight: StackWeight) -> io::Result<()> {
+    // Different captured stacks can be resolved to identical lines, so merge them.
+    let mut lines = BTreeMap::<String, u128>::new();
+    for (frames, stats) in captured_stacks() {
+        let value = match weight {
+            StackWeight::WaitTime => stats.wait_time,
+            StackWeight::HoldTime => stats.hold_time,
+        }
+        .as_nanos();
+        if value == 0 {
+            continue;
+        }
+
+        let stack = frames
+            .iter()
+            .rev()
+            .map(|frame| frame.name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        *lines.entry(stack).or_default() += value;
+    }
+
+    for (stack, value) in lines {
+        writeln!(writer, "{stack} {value}")?;
+    }
+
+    Ok(())
+}
+
+/// A single call stack that guards were used from.
+pub(crate) struct StackEntry {
+    backtrace: Mutex<Backtrace>,
+    num_waits: AtomicU64,
+    wait_time: AtomicU64,
+    hold_time: AtomicU64,
+}
+
+impl StackEntry {
+    /// Registers a wait for a guard that lasted for the given time.
+    pub(crate) fn add_wait(&self, wait_time: Duration) {
+        self.num_waits.fetch_add(1, Ordering::Relaxed);
+        self.wait_time
+            .fetch_add(wait_time.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Registers a hold of a guard that lasted for the given time.
+    pub(crate) fn add_hold(&self, duration: Duration) {
+        self.hold_time
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Captures the current call stack if the capture is currently active.
+pub(crate) fn capture() -> Option<Arc<StackEntry>> {
+    if !CAPTURING.load(Ordering::Acquire) {
+        return None;
+    }
+
+    let backtrace = Backtrace::new_unresolved();
+    let key = backtrace
+        .frames()
+        .iter()
+        .map(|frame| frame.ip() as usize)
+        .collect::<Vec<_>>();
+
+    let entry = STACKS
+        .lock()
+        .unwrap()
+        .entry(key)
+        .or_insert_with(|| {
+            Arc::new(StackEntry {
+                backtrace: Mutex::new(backtrace),
+                num_waits: Default::default(),
+                wait_time: Default::default(),
+                hold_time: Default::default(),
+            })
+        })
+        .clone();
+
+    Some(entry)
+}
+
+/// A single resolved frame of a captured stack.
+pub(crate) struct Frame {
+    pub(crate) address: usize,
+    pub(crate) name: String,
+    pub(crate) file: Option<PathBuf>,
+    pub(crate) line: Option<u32>,
+}
+
+/// The statistics related to a single captured stack.
+pub(crate) struct StackStats {
+    pub(crate) num_waits: u64,
+    pub(crate) wait_time: Duration,
+    pub(crate) hold_time: Duration,
+}
+
+/// Returns all the captured stacks with their innermost frames first, omitting
+/// the frames belonging to `locktick` itself.
+pub(crate) fn captured_stacks() -> Vec<(Vec<Frame>, StackStats)> {
+    let entries = STACKS.lock().unwrap().values().cloned().collect::<Vec<_>>();
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let mut backtrace = entry.backtrace.lock().unwrap();
+            backtrace.resolve();
+
+            let mut frames = Vec::new();
+            for frame in backtrace.frames() {
+                for symbol in frame.symbols() {
+                    let name = symbol
+                        .name()
+                        .map(|name| format!("{name:#}"))
+                        .unwrap_or_else(|| format!("{:#x}", frame.ip() as usize));
+                    frames.push(Frame {
+                        address: frame.ip() as usize,
+                        name,
+                        file: symbol.filename().map(|path| path.to_owned()),
+                        line: symbol.lineno(),
+                    });
+                }
+            }
+
+            // Skip the frames up to the outermost one belonging to locktick.
+            if let Some(idx) = frames
+                .iter()
+                .rposition(|frame| is_locktick_frame(&frame.name))
+            {
+                frames.drain(..=idx);
+            }
+
+            let stats = StackStats {
+                num_waits: entry.num_waits.load(Ordering::Relaxed),
+                wait_time: Duration::from_nanos(entry.wait_time.load(Ordering::Relaxed)),
+                hold_time: Duration::from_nanos(entry.hold_time.load(Ordering::Relaxed)),
+            };
+
+            (frames, stats)
+        })
+        .collect()
+}
+
+// Checks whether the given symbol name belongs to locktick.
+fn is_locktick_frame(name: &str) -> bool {
+    name.starts_with("locktick::") || name.starts_with("<locktick::")
+}
@@ -1,30 +1,50 @@
 use std::{
-    sync::{MutexGuard, PoisonError, RwLockReadGuard, RwLockWriteGuard, TryLockError},
-    time::Instant,
+    sync::{
+        MutexGuard, PoisonError, RwLockReadGuard, RwLockWriteGuard, TryLockError, WaitTimeoutResult,
+    },
+    time::{Duration, Instant},
 };
 
 #[cfg(feature = "tracing")]
 use tracing::trace;
 
-use crate::lock_info::{
-    call_location, GuardKind, Location, LockGuard, LockInfo, LockKind, WaitGuard,
+use crate::{
+    condvar::CondvarInfo,
+    lock_info::{call_location, LockGuard, LockInfo, WaitGuard},
+    sampling::Sampler,
+    types::{GuardKind, Location, LockKind, LockOptions},
 };
 
 #[derive(Debug)]
 pub struct Mutex<T> {
     lock: std::sync::Mutex<T>,
     location: Location,
+    sampler: Sampler,
 }
 
 impl<T> Mutex<T> {
     pub fn new(item: T) -> Self {
+        Self::with_options(item, Default::default())
+    }
+
+    /// Creates the lock with the given options, e.g. a name.
+    pub fn with_options(item: T, options: LockOptions) -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Mutex, options);
         Self {
             lock: std::sync::Mutex::new(item),
-            location: LockInfo::register(LockKind::Mutex),
+            location,
+            sampler,
         }
     }
 
     pub fn lock(&self) -> Result<LockGuard<MutexGuard<'_, T>>, PoisonError<MutexGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .lock()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Lock;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -41,6 +61,7 @@ impl<T> Mutex<T> {
                     &self.location,
                     guard_location,
                     wait_time,
+                    weight,
                 ));
             }
             Err(std::sync::TryLockError::Poisoned(e)) => {
@@ -52,7 +73,7 @@ impl<T> Mutex<T> {
         }
 
         // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
+        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location, weight);
         let guard = self.lock.lock()?;
         let wait_time = timestamp.elapsed();
         Ok(LockGuard::from_wait_guard(guard, wait_guard, wait_time))
@@ -61,6 +82,13 @@ impl<T> Mutex<T> {
     pub fn try_lock(
         &self,
     ) -> Result<LockGuard<MutexGuard<'_, T>>, TryLockError<MutexGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .try_lock()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Lock;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -72,6 +100,7 @@ impl<T> Mutex<T> {
         let timestamp = Instant::now();
         #[allow(clippy::map_identity)]
         let guard = self.lock.try_lock().inspect_err(|_e| {
+            crate::observer::try_lock_failed(&self.location, &guard_location, guard_kind);
             #[cfg(feature = "tracing")]
             trace!(
                 "Failed to acquire a {:?} guard at {guard_location}: {_e}",
@@ -85,15 +114,18 @@ impl<T> Mutex<T> {
             &self.location,
             guard_location,
             wait_time,
+            weight,
         ))
     }
 }
 
 impl<T: Default> Default for Mutex<T> {
     fn default() -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Mutex, Default::default());
         Self {
             lock: Default::default(),
-            location: LockInfo::register(LockKind::Mutex),
+            location,
+            sampler,
         }
     }
 }
@@ -102,19 +134,34 @@ impl<T: Default> Default for Mutex<T> {
 pub struct RwLock<T> {
     lock: std::sync::RwLock<T>,
     location: Location,
+    sampler: Sampler,
 }
 
 impl<T> RwLock<T> {
     pub fn new(item: T) -> Self {
+        Self::with_options(item, Default::default())
+    }
+
+    /// Creates the lock with the given options, e.g. a name.
+    pub fn with_options(item: T, options: LockOptions) -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::RwLock, options);
         Self {
             lock: std::sync::RwLock::new(item),
-            location: LockInfo::register(LockKind::RwLock),
+            location,
+            sampler,
         }
     }
 
     pub fn read(
         &self,
     ) -> Result<LockGuard<RwLockReadGuard<'_, T>>, PoisonError<RwLockReadGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .read()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Read;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -131,6 +178,7 @@ impl<T> RwLock<T> {
                     &self.location,
                     guard_location,
                     wait_time,
+                    weight,
                 ));
             }
             Err(std::sync::TryLockError::Poisoned(e)) => {
@@ -142,7 +190,7 @@ impl<T> RwLock<T> {
         }
 
         // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
+        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location, weight);
         let guard = self.lock.read()?;
         let wait_time = timestamp.elapsed();
         Ok(LockGuard::from_wait_guard(guard, wait_guard, wait_time))
@@ -151,6 +199,13 @@ impl<T> RwLock<T> {
     pub fn try_read(
         &self,
     ) -> Result<LockGuard<RwLockReadGuard<'_, T>>, TryLockError<RwLockReadGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .try_read()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Read;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -161,6 +216,7 @@ impl<T> RwLock<T> {
         );
         let timestamp = Instant::now();
         let guard = self.lock.try_read().inspect_err(|_e| {
+            crate::observer::try_lock_failed(&self.location, &guard_location, guard_kind);
             #[cfg(feature = "tracing")]
             trace!(
                 "Failed to acquire a {:?} guard at {guard_location}: {_e}",
@@ -174,12 +230,20 @@ impl<T> RwLock<T> {
             &self.location,
             guard_location,
             wait_time,
+            weight,
         ))
     }
 
     pub fn write(
         &self,
     ) -> Result<LockGuard<RwLockWriteGuard<'_, T>>, PoisonError<RwLockWriteGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .write()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Write;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -196,6 +260,7 @@ impl<T> RwLock<T> {
                     &self.location,
                     guard_location,
                     wait_time,
+                    weight,
                 ));
             }
             Err(std::sync::TryLockError::Poisoned(e)) => {
@@ -207,7 +272,7 @@ impl<T> RwLock<T> {
         }
 
         // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
+        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location, weight);
         let guard = self.lock.write()?;
         let wait_time = timestamp.elapsed();
         Ok(LockGuard::from_wait_guard(guard, wait_guard, wait_time))
@@ -216,6 +281,13 @@ impl<T> RwLock<T> {
     pub fn try_write(
         &self,
     ) -> Result<LockGuard<RwLockWriteGuard<'_, T>>, TryLockError<RwLockWriteGuard<'_, T>>> {
+        let Some(weight) = self.sampler.sample() else {
+            return self
+                .lock
+                .try_write()
+                .map(|guard| LockGuard::untracked(guard, &self.location));
+        };
+
         let guard_kind = GuardKind::Write;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
@@ -226,6 +298,7 @@ impl<T> RwLock<T> {
         );
         let timestamp = Instant::now();
         let guard = self.lock.try_write().inspect_err(|_e| {
+            crate::observer::try_lock_failed(&self.location, &guard_location, guard_kind);
             #[cfg(feature = "tracing")]
             trace!(
                 "Failed to acquire a {:?} guard at {guard_location}: {_e}",
@@ -239,15 +312,125 @@ impl<T> RwLock<T> {
             &self.location,
             guard_location,
             wait_time,
+            weight,
         ))
     }
 }
 
 impl<T: Default> Default for RwLock<T> {
     fn default() -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::RwLock, Default::default());
         Self {
             lock: Default::default(),
-            location: LockInfo::register(LockKind::RwLock),
+            location,
+            sampler,
+        }
+    }
+}
+
+/// A condition variable that works with the guards of `Mutex`. Waiting on it
+/// releases the guard, and waking up reacquires it as a new use of the guard;
+/// in the meantime, the task is registered as waiting for the guard.
+#[derive(Debug)]
+pub struct Condvar {
+    condvar: std::sync::Condvar,
+    location: Location,
+}
+
+impl Condvar {
+    pub fn new() -> Self {
+        Self {
+            condvar: std::sync::Condvar::new(),
+            location: CondvarInfo::register(),
+        }
+    }
+
+    pub fn wait<'a, T>(
+        &self,
+        guard: LockGuard<MutexGuard<'a, T>>,
+    ) -> Result<LockGuard<MutexGuard<'a, T>>, PoisonError<MutexGuard<'a, T>>> {
+        let (guard, relock) = guard.unlock();
+        let started = CondvarInfo::wait_started(&self.location);
+        let result = self.condvar.wait(guard);
+        let wait_time = started.map_or(Duration::ZERO, |started| {
+            CondvarInfo::wait_finished(&self.location, started, None)
+        });
+        Ok(relock.relock(result?, wait_time))
+    }
+
+    pub fn wait_while<'a, T, F: FnMut(&mut T) -> bool>(
+        &self,
+        mut guard: LockGuard<MutexGuard<'a, T>>,
+        mut condition: F,
+    ) -> Result<LockGuard<MutexGuard<'a, T>>, PoisonError<MutexGuard<'a, T>>> {
+        while condition(&mut *guard) {
+            guard = self.wait(guard)?;
         }
+        Ok(guard)
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: LockGuard<MutexGuard<'a, T>>,
+        timeout: Duration,
+    ) -> Result<
+        (LockGuard<MutexGuard<'a, T>>, WaitTimeoutResult),
+        PoisonError<(MutexGuard<'a, T>, WaitTimeoutResult)>,
+    > {
+        let (guard, relock) = guard.unlock();
+        let started = CondvarInfo::wait_started(&self.location);
+        let result = self.condvar.wait_timeout(guard, timeout);
+        let timed_out = match &result {
+            Ok((_, result)) => result.timed_out(),
+            Err(e) => e.get_ref().1.timed_out(),
+        };
+        let wait_time = started.map_or(Duration::ZERO, |started| {
+            let deadline = timed_out.then(|| started + timeout);
+            CondvarInfo::wait_finished(&self.location, started, deadline)
+        });
+        let (guard, result) = result?;
+        Ok((relock.relock(guard, wait_time), result))
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn wait_timeout_while<'a, T, F: FnMut(&mut T) -> bool>(
+        &self,
+        guard: LockGuard<MutexGuard<'a, T>>,
+        timeout: Duration,
+        condition: F,
+    ) -> Result<
+        (LockGuard<MutexGuard<'a, T>>, WaitTimeoutResult),
+        PoisonError<(MutexGuard<'a, T>, WaitTimeoutResult)>,
+    > {
+        let (guard, relock) = guard.unlock();
+        let started = CondvarInfo::wait_started(&self.location);
+        let result = self.condvar.wait_timeout_while(guard, timeout, condition);
+        let timed_out = match &result {
+            Ok((_, result)) => result.timed_out(),
+            Err(e) => e.get_ref().1.timed_out(),
+        };
+        let wait_time = started.map_or(Duration::ZERO, |started| {
+            let deadline = timed_out.then(|| started + timeout);
+            CondvarInfo::wait_finished(&self.location, started, deadline)
+        });
+        let (guard, result) = result?;
+        Ok((relock.relock(guard, wait_time), result))
+    }
+
+    pub fn notify_one(&self) {
+        CondvarInfo::notified(&self.location, false);
+        self.condvar.notify_one();
+    }
+
+    pub fn notify_all(&self) {
+        CondvarInfo::notified(&self.location, true);
+        self.condvar.notify_all();
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -1,81 +1,108 @@
-use std::time::Instant;
+use std::{
+    future::Future,
+    mem,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+    time::Instant,
+};
+
+use pin_project_lite::pin_project;
 
-use tokio::sync::{MutexGuard, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+use tokio::sync::{
+    AcquireError, MappedMutexGuard, MutexGuard, OwnedSemaphorePermit, RwLockMappedWriteGuard,
+    RwLockReadGuard, RwLockWriteGuard, SemaphorePermit, TryAcquireError, TryLockError,
+};
 #[cfg(feature = "tracing")]
 use tracing::trace;
 
-use crate::lock_info::{
-    call_location, GuardKind, Location, LockGuard, LockInfo, LockKind, WaitGuard,
+use crate::{
+    lock_info::{call_location, LockGuard, LockInfo, WaitGuard},
+    sampling::Sampler,
+    types::{GuardKind, Location, LockKind, LockOptions},
 };
 
 #[derive(Debug)]
 pub struct Mutex<T> {
     lock: tokio::sync::Mutex<T>,
     location: Location,
+    sampler: Sampler,
 }
 
 impl<T> Mutex<T> {
     pub fn new(item: T) -> Self {
+        Self::with_options(item, Default::default())
+    }
+
+    /// Creates the lock with the given options, e.g. a name.
+    pub fn with_options(item: T, options: LockOptions) -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Mutex, options);
         Self {
             lock: tokio::sync::Mutex::new(item),
-            location: LockInfo::register(LockKind::Mutex),
+            location,
+            sampler,
         }
     }
 
     pub async fn lock(&self) -> LockGuard<MutexGuard<'_, T>> {
-        let guard_kind = GuardKind::Lock;
-        let guard_location = call_location();
-        #[cfg(feature = "tracing")]
-        trace!("Acquiring a {:?} guard at {}", guard_kind, guard_location);
-
-        // Fast path -- try to acquire lock without blocking first
-        let timestamp = Instant::now();
-        if let Ok(guard) = self.lock.try_lock() {
-            let wait_time = timestamp.elapsed();
-            return LockGuard::new(guard, guard_kind, &self.location, guard_location, wait_time);
-        }
-
-        // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
-        let guard = self.lock.lock().await;
-        let wait_time = timestamp.elapsed();
-        LockGuard::from_wait_guard(guard, wait_guard, wait_time)
+        acquire_guard(
+            &self.location,
+            &self.sampler,
+            GuardKind::Lock,
+            || self.lock.try_lock(),
+            || self.lock.lock(),
+        )
+        .await
     }
 
     pub fn try_lock(&self) -> Result<LockGuard<MutexGuard<'_, T>>, TryLockError> {
-        let guard_kind = GuardKind::Lock;
-        let guard_location = call_location();
-        #[cfg(feature = "tracing")]
-        trace!(
-            "Attempting to acquire a {:?} guard at {}",
-            guard_kind,
-            guard_location
-        );
-        let timestamp = Instant::now();
-        #[allow(clippy::map_identity)]
-        let guard = self.lock.try_lock().inspect_err(|e| {
-            #[cfg(feature = "tracing")]
-            trace!(
-                "Failed to acquire a {:?} guard at {guard_location}: {e}",
-                guard_kind,
-            );
-        })?;
-        let wait_time = timestamp.elapsed();
-        Ok(LockGuard::new(
-            guard,
-            guard_kind,
+        try_acquire_guard(&self.location, &self.sampler, GuardKind::Lock, || {
+            self.lock.try_lock()
+        })
+    }
+
+    pub async fn lock_owned(self: Arc<Self>) -> LockGuard<OwnedMutexGuard<T>>
+    where
+        T: 'static,
+    {
+        acquire_guard(
             &self.location,
-            guard_location,
-            wait_time,
-        ))
+            &self.sampler,
+            GuardKind::Lock,
+            || {
+                let guard = self.lock.try_lock()?;
+                // SAFETY: the guard is borrowed from the lock in `self`.
+                Ok(unsafe { OwnedMutexGuard::new(guard, self.clone()) })
+            },
+            || async {
+                let guard = self.lock.lock().await;
+                // SAFETY: the guard is borrowed from the lock in `self`.
+                unsafe { OwnedMutexGuard::new(guard, self.clone()) }
+            },
+        )
+        .await
+    }
+
+    pub fn try_lock_owned(self: Arc<Self>) -> Result<LockGuard<OwnedMutexGuard<T>>, TryLockError>
+    where
+        T: 'static,
+    {
+        try_acquire_guard(&self.location, &self.sampler, GuardKind::Lock, || {
+            let guard = self.lock.try_lock()?;
+            // SAFETY: the guard is borrowed from the lock in `self`.
+            Ok(unsafe { OwnedMutexGuard::new(guard, self.clone()) })
+        })
     }
 }
 
 impl<T: Default> Default for Mutex<T> {
     fn default() -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Mutex, Default::default());
         Self {
             lock: Default::default(),
-            location: LockInfo::register(LockKind::Mutex),
+            location,
+            sampler,
         }
     }
 }
@@ -84,85 +111,608 @@ impl<T: Default> Default for Mutex<T> {
 pub struct RwLock<T> {
     lock: tokio::sync::RwLock<T>,
     location: Location,
+    sampler: Sampler,
 }
 
 impl<T> RwLock<T> {
     pub fn new(item: T) -> Self {
+        Self::with_options(item, Default::default())
+    }
+
+    /// Creates the lock with the given options, e.g. a name.
+    pub fn with_options(item: T, options: LockOptions) -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::RwLock, options);
         Self {
             lock: tokio::sync::RwLock::new(item),
-            location: LockInfo::register(LockKind::RwLock),
+            location,
+            sampler,
         }
     }
 
     pub async fn read(&self) -> LockGuard<RwLockReadGuard<'_, T>> {
-        let guard_kind = GuardKind::Read;
-        let guard_location = call_location();
-        #[cfg(feature = "tracing")]
-        trace!("Acquiring a {:?} guard at {}", guard_kind, guard_location);
+        acquire_guard(
+            &self.location,
+            &self.sampler,
+            GuardKind::Read,
+            || self.lock.try_read(),
+            || self.lock.read(),
+        )
+        .await
+    }
 
-        // Fast path -- try to acquire lock without blocking first
-        let timestamp = Instant::now();
-        if let Ok(guard) = self.lock.try_read() {
-            let wait_time = timestamp.elapsed();
-            return LockGuard::new(guard, guard_kind, &self.location, guard_location, wait_time);
+    pub fn try_read(&self) -> Result<LockGuard<RwLockReadGuard<'_, T>>, TryLockError> {
+        try_acquire_guard(&self.location, &self.sampler, GuardKind::Read, || {
+            self.lock.try_read()
+        })
+    }
+
+    pub async fn write(&self) -> LockGuard<RwLockWriteGuard<'_, T>> {
+        acquire_guard(
+            &self.location,
+            &self.sampler,
+            GuardKind::Write,
+            || self.lock.try_write(),
+            || self.lock.write(),
+        )
+        .await
+    }
+
+    pub fn try_write(&self) -> Result<LockGuard<RwLockWriteGuard<'_, T>>, TryLockError> {
+        try_acquire_guard(&self.location, &self.sampler, GuardKind::Write, || {
+            self.lock.try_write()
+        })
+    }
+
+    pub async fn read_owned(self: Arc<Self>) -> LockGuard<OwnedRwLockReadGuard<T>>
+    where
+        T: 'static,
+    {
+        acquire_guard(
+            &self.location,
+            &self.sampler,
+            GuardKind::Read,
+            || {
+                let guard = self.lock.try_read()?;
+                // SAFETY: the guard is borrowed from the lock in `self`.
+                Ok(unsafe { OwnedRwLockReadGuard::new(guard, self.clone()) })
+            },
+            || async {
+                let guard = self.lock.read().await;
+                // SAFETY: the guard is borrowed from the lock in `self`.
+                unsafe { OwnedRwLockReadGuard::new(guard, self.clone()) }
+            },
+        )
+        .await
+    }
+
+    pub fn try_read_owned(
+        self: Arc<Self>,
+    ) -> Result<LockGuard<OwnedRwLockReadGuard<T>>, TryLockError>
+    where
+        T: 'static,
+    {
+        try_acquire_guard(&self.location, &self.sampler, GuardKind::Read, || {
+            let guard = self.lock.try_read()?;
+            // SAFETY: the guard is borrowed from the lock in `self`.
+            Ok(unsafe { OwnedRwLockReadGuard::new(guard, self.clone()) })
+        })
+    }
+
+    pub async fn write_owned(self: Arc<Self>) -> LockGuard<OwnedRwLockWriteGuard<T>>
+    where
+        T: 'static,
+    {
+        acquire_guard(
+            &self.location,
+            &self.sampler,
+            GuardKind::Write,
+            || {
+                let guard = self.lock.try_write()?;
+                // SAFETY: the guard is borrowed from the lock in `self`.
+                Ok(unsafe { OwnedRwLockWriteGuard::new(guard, self.clone()) })
+            },
+            || async {
+                let guard = self.lock.write().await;
+                // SAFETY: the guard is borrowed from the lock in `self`.
+                unsafe { OwnedRwLockWriteGuard::new(guard, self.clone()) }
+            },
+        )
+        .await
+    }
+
+    pub fn try_write_owned(
+        self: Arc<Self>,
+    ) -> Result<LockGuard<OwnedRwLockWriteGuard<T>>, TryLockError>
+    where
+        T: 'static,
+    {
+        try_acquire_guard(&self.location, &self.sampler, GuardKind::Write, || {
+            let guard = self.lock.try_write()?;
+            // SAFETY: the guard is borrowed from the lock in `self`.
+            Ok(unsafe { OwnedRwLockWriteGuard::new(guard, self.clone()) })
+        })
+    }
+
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::RwLock, Default::default());
+        Self {
+            lock: Default::default(),
+            location,
+            sampler,
         }
+    }
+}
+
+// Acquires a guard of the given kind, trying to do so without waiting first.
+async fn acquire_guard<G, F: Future<Output = G>>(
+    location: &Location,
+    sampler: &Sampler,
+    guard_kind: GuardKind,
+    try_acquire: impl FnOnce() -> Result<G, TryLockError>,
+    acquire: impl FnOnce() -> F,
+) -> LockGuard<G> {
+    let Some(weight) = sampler.sample() else {
+        return LockGuard::untracked(acquire().await, location);
+    };
 
-        // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
-        let guard = self.lock.read().await;
+    let guard_location = call_location();
+    #[cfg(feature = "tracing")]
+    trace!("Acquiring a {:?} guard at {}", guard_kind, guard_location);
+
+    // Fast path -- try to acquire lock without blocking first
+    let timestamp = Instant::now();
+    if let Ok(guard) = try_acquire() {
         let wait_time = timestamp.elapsed();
-        LockGuard::from_wait_guard(guard, wait_guard, wait_time)
+        return LockGuard::new(
+            guard,
+            guard_kind,
+            location,
+            guard_location,
+            wait_time,
+            weight,
+        );
     }
 
-    pub fn try_read(&self) -> Result<LockGuard<RwLockReadGuard<'_, T>>, TryLockError> {
-        let guard_kind = GuardKind::Read;
-        let guard_location = call_location();
+    // Lock is contended, create WaitGuard and block
+    let wait_guard = WaitGuard::new(guard_kind, location, guard_location, weight);
+    let guard = acquire().await;
+    let wait_time = timestamp.elapsed();
+    LockGuard::from_wait_guard(guard, wait_guard, wait_time)
+}
+
+// Attempts to acquire a guard of the given kind without waiting.
+fn try_acquire_guard<G>(
+    location: &Location,
+    sampler: &Sampler,
+    guard_kind: GuardKind,
+    try_acquire: impl FnOnce() -> Result<G, TryLockError>,
+) -> Result<LockGuard<G>, TryLockError> {
+    let Some(weight) = sampler.sample() else {
+        return try_acquire().map(|guard| LockGuard::untracked(guard, location));
+    };
+
+    let guard_location = call_location();
+    #[cfg(feature = "tracing")]
+    trace!(
+        "Attempting to acquire a {:?} guard at {}",
+        guard_kind,
+        guard_location
+    );
+    let timestamp = Instant::now();
+    let guard = try_acquire().inspect_err(|_e| {
+        crate::observer::try_lock_failed(location, &guard_location, guard_kind);
         #[cfg(feature = "tracing")]
         trace!(
-            "Attempting to acquire a {:?} guard at {}",
+            "Failed to acquire a {:?} guard at {guard_location}: {_e}",
             guard_kind,
-            guard_location
         );
-        let timestamp = Instant::now();
-        let guard = self.lock.try_read().inspect_err(|e| {
-            #[cfg(feature = "tracing")]
-            trace!(
-                "Failed to acquire a {:?} guard at {guard_location}: {e}",
-                guard_kind,
-            );
-        })?;
-        let wait_time = timestamp.elapsed();
-        Ok(LockGuard::new(
-            guard,
-            guard_kind,
-            &self.location,
-            guard_location,
-            wait_time,
-        ))
+    })?;
+    let wait_time = timestamp.elapsed();
+    Ok(LockGuard::new(
+        guard,
+        guard_kind,
+        location,
+        guard_location,
+        wait_time,
+        weight,
+    ))
+}
+
+// The owned guards store the guards of the underlying locks along with the
+// `Arc`s of the wrappers they borrow from, which keeps the locks stored inline.
+// The borrows are extended to `'static`, which is sound as long as the guards
+// are dropped before the `Arc`s (i.e. they are declared first), and their
+// lifetimes never escape the owned guards.
+
+/// An owned guard of a `Mutex`, which keeps the lock alive.
+#[derive(Debug)]
+pub struct OwnedMutexGuard<T: 'static> {
+    guard: MutexGuard<'static, T>,
+    lock: Arc<Mutex<T>>,
+}
+
+impl<T> OwnedMutexGuard<T> {
+    // SAFETY: the guard must be borrowed from the given lock.
+    unsafe fn new(guard: MutexGuard<'_, T>, lock: Arc<Mutex<T>>) -> Self {
+        Self {
+            guard: unsafe { mem::transmute::<MutexGuard<'_, T>, MutexGuard<'static, T>>(guard) },
+            lock,
+        }
     }
 
-    pub async fn write(&self) -> LockGuard<RwLockWriteGuard<'_, T>> {
-        let guard_kind = GuardKind::Write;
+    /// Returns the `Mutex` that the guard was acquired from.
+    pub fn mutex(this: &Self) -> &Arc<Mutex<T>> {
+        &this.lock
+    }
+
+    /// Makes a guard for a component of the locked data.
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> OwnedMappedMutexGuard<T, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        OwnedMappedMutexGuard {
+            guard: MutexGuard::map(this.guard, f),
+            lock: this.lock,
+        }
+    }
+}
+
+impl<T> Deref for OwnedMutexGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for OwnedMutexGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+/// An owned guard of a component of the data locked by a `Mutex`, produced by
+/// `OwnedMutexGuard::map`.
+#[derive(Debug)]
+pub struct OwnedMappedMutexGuard<T: 'static, U: ?Sized + 'static> {
+    guard: MappedMutexGuard<'static, U>,
+    lock: Arc<Mutex<T>>,
+}
+
+impl<T, U: ?Sized> OwnedMappedMutexGuard<T, U> {
+    /// Returns the `Mutex` that the guard was acquired from.
+    pub fn mutex(this: &Self) -> &Arc<Mutex<T>> {
+        &this.lock
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedMappedMutexGuard<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        &self.guard
+    }
+}
+
+impl<T, U: ?Sized> DerefMut for OwnedMappedMutexGuard<T, U> {
+    fn deref_mut(&mut self) -> &mut U {
+        &mut self.guard
+    }
+}
+
+/// An owned read guard of a `RwLock`, which keeps the lock alive; the data it
+/// points to can be narrowed down to a component via `OwnedRwLockReadGuard::map`.
+#[derive(Debug)]
+pub struct OwnedRwLockReadGuard<T: 'static, U: ?Sized + 'static = T> {
+    guard: RwLockReadGuard<'static, U>,
+    lock: Arc<RwLock<T>>,
+}
+
+impl<T> OwnedRwLockReadGuard<T> {
+    // SAFETY: the guard must be borrowed from the given lock.
+    unsafe fn new(guard: RwLockReadGuard<'_, T>, lock: Arc<RwLock<T>>) -> Self {
+        Self {
+            guard: unsafe {
+                mem::transmute::<RwLockReadGuard<'_, T>, RwLockReadGuard<'static, T>>(guard)
+            },
+            lock,
+        }
+    }
+}
+
+impl<T, U: ?Sized> OwnedRwLockReadGuard<T, U> {
+    /// Returns the `RwLock` that the guard was acquired from.
+    pub fn rwlock(this: &Self) -> &Arc<RwLock<T>> {
+        &this.lock
+    }
+
+    /// Makes a guard for a component of the locked data.
+    pub fn map<V: ?Sized, F>(this: Self, f: F) -> OwnedRwLockReadGuard<T, V>
+    where
+        F: FnOnce(&U) -> &V,
+    {
+        OwnedRwLockReadGuard {
+            guard: RwLockReadGuard::map(this.guard, f),
+            lock: this.lock,
+        }
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedRwLockReadGuard<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        &self.guard
+    }
+}
+
+/// An owned write guard of a `RwLock`, which keeps the lock alive.
+#[derive(Debug)]
+pub struct OwnedRwLockWriteGuard<T: 'static> {
+    guard: RwLockWriteGuard<'static, T>,
+    lock: Arc<RwLock<T>>,
+}
+
+impl<T> OwnedRwLockWriteGuard<T> {
+    // SAFETY: the guard must be borrowed from the given lock.
+    unsafe fn new(guard: RwLockWriteGuard<'_, T>, lock: Arc<RwLock<T>>) -> Self {
+        Self {
+            guard: unsafe {
+                mem::transmute::<RwLockWriteGuard<'_, T>, RwLockWriteGuard<'static, T>>(guard)
+            },
+            lock,
+        }
+    }
+
+    /// Returns the `RwLock` that the guard was acquired from.
+    pub fn rwlock(this: &Self) -> &Arc<RwLock<T>> {
+        &this.lock
+    }
+
+    /// Makes a guard for a component of the locked data.
+    pub fn map<U: ?Sized, F>(this: Self, f: F) -> OwnedRwLockMappedWriteGuard<T, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        OwnedRwLockMappedWriteGuard {
+            guard: RwLockWriteGuard::map(this.guard, f),
+            lock: this.lock,
+        }
+    }
+
+    /// Atomically turns the guard into a read guard, without allowing any
+    /// writers to acquire the lock in the meantime.
+    pub fn downgrade(self) -> OwnedRwLockReadGuard<T> {
+        OwnedRwLockReadGuard {
+            guard: self.guard.downgrade(),
+            lock: self.lock,
+        }
+    }
+}
+
+impl<T> Deref for OwnedRwLockWriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for OwnedRwLockWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+/// An owned write guard of a component of the data locked by a `RwLock`,
+/// produced by `OwnedRwLockWriteGuard::map`.
+#[derive(Debug)]
+pub struct OwnedRwLockMappedWriteGuard<T: 'static, U: ?Sized + 'static> {
+    guard: RwLockMappedWriteGuard<'static, U>,
+    lock: Arc<RwLock<T>>,
+}
+
+impl<T, U: ?Sized> OwnedRwLockMappedWriteGuard<T, U> {
+    /// Returns the `RwLock` that the guard was acquired from.
+    pub fn rwlock(this: &Self) -> &Arc<RwLock<T>> {
+        &this.lock
+    }
+}
+
+impl<T, U: ?Sized> Deref for OwnedRwLockMappedWriteGuard<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        &self.guard
+    }
+}
+
+impl<T, U: ?Sized> DerefMut for OwnedRwLockMappedWriteGuard<T, U> {
+    fn deref_mut(&mut self) -> &mut U {
+        &mut self.guard
+    }
+}
+
+#[derive(Debug)]
+pub struct Semaphore {
+    // Shared with the owned permits.
+    semaphore: Arc<tokio::sync::Semaphore>,
+    location: Location,
+    sampler: Sampler,
+}
+
+impl Semaphore {
+    pub fn new(permits: usize) -> Self {
+        Self::with_options(permits, Default::default())
+    }
+
+    /// Creates the semaphore with the given options, e.g. a name.
+    pub fn with_options(permits: usize, options: LockOptions) -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Semaphore, options);
+        LockInfo::update_permits(&location, |info| info.available = permits);
+        Self {
+            semaphore: Arc::new(tokio::sync::Semaphore::new(permits)),
+            location,
+            sampler,
+        }
+    }
+
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    pub fn add_permits(&self, n: usize) {
+        self.semaphore.add_permits(n);
+        LockInfo::update_permits(&self.location, |info| {
+            info.available = self.semaphore.available_permits()
+        });
+    }
+
+    pub fn close(&self) {
+        self.semaphore.close();
+        LockInfo::update_permits(&self.location, |info| {
+            info.available = self.semaphore.available_permits()
+        });
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.semaphore.is_closed()
+    }
+
+    pub async fn acquire(&self) -> Result<LockGuard<SemaphorePermit<'_>>, AcquireError> {
+        self.acquire_permits(
+            1,
+            || self.semaphore.try_acquire(),
+            || self.semaphore.acquire(),
+        )
+        .await
+    }
+
+    pub async fn acquire_many(
+        &self,
+        n: u32,
+    ) -> Result<LockGuard<SemaphorePermit<'_>>, AcquireError> {
+        self.acquire_permits(
+            n,
+            || self.semaphore.try_acquire_many(n),
+            || self.semaphore.acquire_many(n),
+        )
+        .await
+    }
+
+    pub fn try_acquire(&self) -> Result<LockGuard<SemaphorePermit<'_>>, TryAcquireError> {
+        self.try_acquire_permits(1, || self.semaphore.try_acquire())
+    }
+
+    pub fn try_acquire_many(
+        &self,
+        n: u32,
+    ) -> Result<LockGuard<SemaphorePermit<'_>>, TryAcquireError> {
+        self.try_acquire_permits(n, || self.semaphore.try_acquire_many(n))
+    }
+
+    pub async fn acquire_owned(
+        self: Arc<Self>,
+    ) -> Result<LockGuard<OwnedSemaphorePermit>, AcquireError> {
+        self.acquire_permits(
+            1,
+            || self.semaphore.clone().try_acquire_owned(),
+            || self.semaphore.clone().acquire_owned(),
+        )
+        .await
+    }
+
+    pub async fn acquire_many_owned(
+        self: Arc<Self>,
+        n: u32,
+    ) -> Result<LockGuard<OwnedSemaphorePermit>, AcquireError> {
+        self.acquire_permits(
+            n,
+            || self.semaphore.clone().try_acquire_many_owned(n),
+            || self.semaphore.clone().acquire_many_owned(n),
+        )
+        .await
+    }
+
+    pub fn try_acquire_owned(
+        self: Arc<Self>,
+    ) -> Result<LockGuard<OwnedSemaphorePermit>, TryAcquireError> {
+        self.try_acquire_permits(1, || self.semaphore.clone().try_acquire_owned())
+    }
+
+    pub fn try_acquire_many_owned(
+        self: Arc<Self>,
+        n: u32,
+    ) -> Result<LockGuard<OwnedSemaphorePermit>, TryAcquireError> {
+        self.try_acquire_permits(n, || self.semaphore.clone().try_acquire_many_owned(n))
+    }
+
+    // Acquires the given number of permits, trying to do so without waiting first.
+    async fn acquire_permits<P, F: Future<Output = Result<P, AcquireError>>>(
+        &self,
+        permits: u32,
+        try_acquire: impl FnOnce() -> Result<P, TryAcquireError>,
+        acquire: impl FnOnce() -> F,
+    ) -> Result<LockGuard<P>, AcquireError> {
+        let Some(weight) = self.sampler.sample() else {
+            return acquire()
+                .await
+                .map(|permit| LockGuard::untracked(permit, &self.location));
+        };
+
+        let guard_kind = GuardKind::Permit;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
         trace!("Acquiring a {:?} guard at {}", guard_kind, guard_location);
 
-        // Fast path -- try to acquire lock without blocking first
+        // Fast path -- try to acquire the permits without waiting first
         let timestamp = Instant::now();
-        if let Ok(guard) = self.lock.try_write() {
-            let wait_time = timestamp.elapsed();
-            return LockGuard::new(guard, guard_kind, &self.location, guard_location, wait_time);
-        }
+        let guard = match try_acquire() {
+            Ok(permit) => {
+                let wait_time = timestamp.elapsed();
+                LockGuard::new(
+                    permit,
+                    guard_kind,
+                    &self.location,
+                    guard_location,
+                    wait_time,
+                    weight,
+                )
+            }
+            // The semaphore is closed, which the regular acquisition reports.
+            Err(TryAcquireError::Closed) => {
+                return acquire()
+                    .await
+                    .map(|permit| LockGuard::untracked(permit, &self.location));
+            }
+            Err(TryAcquireError::NoPermits) => {
+                // The permits are contended, create WaitGuard and wait
+                let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location, weight);
+                let permit = acquire().await?;
+                let wait_time = timestamp.elapsed();
+                LockGuard::from_wait_guard(permit, wait_guard, wait_time)
+            }
+        };
 
-        // Lock is contended, create WaitGuard and block
-        let wait_guard = WaitGuard::new(guard_kind, &self.location, guard_location);
-        let guard = self.lock.write().await;
-        let wait_time = timestamp.elapsed();
-        LockGuard::from_wait_guard(guard, wait_guard, wait_time)
+        Ok(guard.with_permits(permits as usize, &self.semaphore))
     }
 
-    pub fn try_write(&self) -> Result<LockGuard<RwLockWriteGuard<'_, T>>, TryLockError> {
-        let guard_kind = GuardKind::Write;
+    fn try_acquire_permits<P>(
+        &self,
+        permits: u32,
+        try_acquire: impl FnOnce() -> Result<P, TryAcquireError>,
+    ) -> Result<LockGuard<P>, TryAcquireError> {
+        let Some(weight) = self.sampler.sample() else {
+            return try_acquire().map(|permit| LockGuard::untracked(permit, &self.location));
+        };
+
+        let guard_kind = GuardKind::Permit;
         let guard_location = call_location();
         #[cfg(feature = "tracing")]
         trace!(
@@ -171,33 +721,133 @@ impl<T> RwLock<T> {
             guard_location
         );
         let timestamp = Instant::now();
-        let guard = self.lock.try_write().inspect_err(|e| {
+        let permit = try_acquire().inspect_err(|_e| {
+            crate::observer::try_lock_failed(&self.location, &guard_location, guard_kind);
             #[cfg(feature = "tracing")]
             trace!(
-                "Failed to acquire a {:?} guard at {guard_location}: {e}",
+                "Failed to acquire a {:?} guard at {guard_location}: {_e}",
                 guard_kind,
             );
         })?;
         let wait_time = timestamp.elapsed();
-        Ok(LockGuard::new(
-            guard,
+        let guard = LockGuard::new(
+            permit,
             guard_kind,
             &self.location,
             guard_location,
             wait_time,
-        ))
+            weight,
+        );
+
+        Ok(guard.with_permits(permits as usize, &self.semaphore))
     }
+}
 
-    pub fn into_inner(self) -> T {
-        self.lock.into_inner()
+#[derive(Debug)]
+pub struct Notify {
+    notify: tokio::sync::Notify,
+    location: Location,
+    sampler: Sampler,
+}
+
+impl Notify {
+    pub fn new() -> Self {
+        Self::with_options(Default::default())
+    }
+
+    /// Creates the `Notify` with the given options, e.g. a name.
+    pub fn with_options(options: LockOptions) -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Notify, options);
+        Self {
+            notify: tokio::sync::Notify::new(),
+            location,
+            sampler,
+        }
+    }
+
+    /// Waits for a notification; like with `tokio::sync::Notify::notified`,
+    /// the calls to `notify_waiters` made once this method is called are
+    /// received, even before the returned future is polled. The wait starts
+    /// once the future is first polled, and is considered cancelled if the
+    /// future is dropped before a notification is received.
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notified: self.notify.notified(),
+            location: &self.location,
+            tracked: self
+                .sampler
+                .sample()
+                .map(|weight| (weight, call_location())),
+            wait: None,
+        }
+    }
+
+    pub fn notify_one(&self) {
+        LockInfo::update_notifications(&self.location, |info| info.notify_one += 1);
+        self.notify.notify_one();
+    }
+
+    pub fn notify_waiters(&self) {
+        LockInfo::update_notifications(&self.location, |info| info.notify_waiters += 1);
+        self.notify.notify_waiters();
     }
 }
 
-impl<T: Default> Default for RwLock<T> {
+pin_project! {
+    /// The future returned by `Notify::notified`.
+    pub struct Notified<'a> {
+        #[pin]
+        notified: tokio::sync::futures::Notified<'a>,
+        location: &'a Location,
+        // The weight of the wait and the location it was requested at, until
+        // it starts; `None` if it isn't tracked.
+        tracked: Option<(usize, Location)>,
+        // The ongoing wait, along with its start.
+        wait: Option<(Instant, WaitGuard)>,
+    }
+}
+
+impl Notified<'_> {
+    /// Like `tokio::sync::Notified::enable`, ensures that the future receives
+    /// the calls to `notify_one` made before it is first polled; it doesn't
+    /// start the wait.
+    pub fn enable(self: Pin<&mut Self>) -> bool {
+        self.project().notified.enable()
+    }
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.project();
+        if let Some((weight, guard_location)) = this.tracked.take() {
+            #[cfg(feature = "tracing")]
+            trace!("Waiting for a notification at {}", guard_location);
+
+            let timestamp = Instant::now();
+            let wait_guard =
+                WaitGuard::new(GuardKind::Notified, this.location, guard_location, weight);
+            *this.wait = Some((timestamp, wait_guard));
+        }
+
+        ready!(this.notified.poll(cx));
+        // There is nothing to hold, so the use of the guard ends with the wait.
+        if let Some((timestamp, wait_guard)) = this.wait.take() {
+            wait_guard.complete(timestamp.elapsed());
+        }
+
+        Poll::Ready(())
+    }
+}
+
+impl Default for Notify {
     fn default() -> Self {
+        let (location, sampler) = LockInfo::register(LockKind::Notify, Default::default());
         Self {
-            lock: Default::default(),
-            location: LockInfo::register(LockKind::RwLock),
+            notify: Default::default(),
+            location,
+            sampler,
         }
     }
 }
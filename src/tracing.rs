@@ -0,0 +1,440 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "tracing_spans")]
+use std::{any::TypeId, fmt, marker::PhantomData, sync::Arc};
+
+use tracing::{event, field::Empty, trace_span, Level, Span};
+#[cfg(feature = "tracing_spans")]
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Dispatch, Subscriber,
+};
+#[cfg(feature = "tracing_spans")]
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+use crate::types::{GuardKind, Location};
+
+/// Creates a `lock.wait` span, which lasts for as long as a task is waiting
+/// to acquire a guard; its `wait_time_ns` (and, for waits that don't end with
+/// an acquisition, `cancelled`) field is recorded when the wait is over.
+pub(crate) fn wait_span(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+) -> Span {
+    trace_span!(
+        "lock.wait",
+        lock.location = %lock_location,
+        guard.location = %guard_location,
+        guard.kind = ?guard_kind,
+        guard.index = guard_index,
+        wait_time_ns = Empty,
+        cancelled = Empty,
+    )
+}
+
+/// Creates a `lock.hold` span, which lasts for as long as a guard is held;
+/// its `duration_ns` field is recorded when the guard is dropped.
+pub(crate) fn hold_span(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    wait_time: Duration,
+) -> Span {
+    trace_span!(
+        "lock.hold",
+        lock.location = %lock_location,
+        guard.location = %guard_location,
+        guard.kind = ?guard_kind,
+        guard.index = guard_index,
+        wait_time_ns = wait_time.as_nanos() as u64,
+        duration_ns = Empty,
+    )
+}
+
+/// The configuration of the events emitted when waiting for or holding
+/// a guard takes longer than the applicable threshold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thresholds {
+    /// The wait time above which an event is emitted; `None` disables such events.
+    pub wait: Option<Duration>,
+    /// The hold time above which an event is emitted; `None` disables such events.
+    pub hold: Option<Duration>,
+    /// The level of the emitted events.
+    pub level: Level,
+    /// The minimum time between events emitted for a single guard location;
+    /// the events suppressed in the meantime are counted in the next one.
+    pub min_interval: Duration,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            wait: None,
+            hold: None,
+            level: Level::WARN,
+            min_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+// The currently applicable thresholds, if any.
+static THRESHOLDS: RwLock<Option<Thresholds>> = RwLock::new(None);
+
+// Allows the thresholds to be skipped without locking if there are none.
+static HAS_THRESHOLDS: AtomicBool = AtomicBool::new(false);
+
+// The time of the most recently emitted threshold event and the number of
+// the suppressed ones, for a single guard location and kind of event.
+struct RateLimit {
+    last_emitted: Instant,
+    suppressed: usize,
+}
+
+// The rate limits for each guard location and kind of event (wait or hold).
+static RATE_LIMITS: LazyLock<Mutex<HashMap<(Location, bool), RateLimit>>> =
+    LazyLock::new(Default::default);
+
+/// Sets the thresholds for the waits and holds which should result in events
+/// being emitted at the configured level; `None` disables such events.
+pub fn set_thresholds(thresholds: Option<Thresholds>) {
+    let mut current = THRESHOLDS.write().unwrap();
+    HAS_THRESHOLDS.store(thresholds.is_some(), Ordering::Relaxed);
+    *current = thresholds;
+    drop(current);
+    RATE_LIMITS.lock().unwrap().clear();
+}
+
+/// Returns the currently applicable thresholds, if any.
+pub fn thresholds() -> Option<Thresholds> {
+    THRESHOLDS.read().unwrap().clone()
+}
+
+/// Emits an event if the wait time exceeds the applicable threshold.
+pub(crate) fn check_wait(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    wait_time: Duration,
+) {
+    check_threshold(
+        true,
+        lock_location,
+        guard_location,
+        guard_kind,
+        guard_index,
+        wait_time,
+    );
+}
+
+/// Emits an event if the hold time exceeds the applicable threshold.
+pub(crate) fn check_hold(
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    duration: Duration,
+) {
+    check_threshold(
+        false,
+        lock_location,
+        guard_location,
+        guard_kind,
+        guard_index,
+        duration,
+    );
+}
+
+fn check_threshold(
+    is_wait: bool,
+    lock_location: &Location,
+    guard_location: &Location,
+    guard_kind: GuardKind,
+    guard_index: usize,
+    time: Duration,
+) {
+    if !HAS_THRESHOLDS.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let (threshold, level, min_interval) = match &*THRESHOLDS.read().unwrap() {
+        Some(thresholds) => {
+            let threshold = if is_wait {
+                thresholds.wait
+            } else {
+                thresholds.hold
+            };
+            match threshold {
+                Some(threshold) if time > threshold => {
+                    (threshold, thresholds.level, thresholds.min_interval)
+                }
+                _ => return,
+            }
+        }
+        None => return,
+    };
+
+    let suppressed = match RATE_LIMITS
+        .lock()
+        .unwrap()
+        .entry((guard_location.clone(), is_wait))
+    {
+        Entry::Vacant(entry) => {
+            entry.insert(RateLimit {
+                last_emitted: Instant::now(),
+                suppressed: 0,
+            });
+            0
+        }
+        Entry::Occupied(mut entry) => {
+            let rate_limit = entry.get_mut();
+            if rate_limit.last_emitted.elapsed() < min_interval {
+                rate_limit.suppressed += 1;
+                return;
+            }
+            rate_limit.last_emitted = Instant::now();
+            std::mem::take(&mut rate_limit.suppressed)
+        }
+    };
+
+    macro_rules! emit {
+        ($level:expr) => {
+            if is_wait {
+                event!(
+                    $level,
+                    lock.location = %lock_location,
+                    guard.location = %guard_location,
+                    guard.kind = ?guard_kind,
+                    guard.index = guard_index,
+                    wait_time_ns = time.as_nanos() as u64,
+                    threshold_ns = threshold.as_nanos() as u64,
+                    suppressed,
+                    "Waited {:?} for a {:?} guard at {}",
+                    time,
+                    guard_kind,
+                    guard_location,
+                )
+            } else {
+                event!(
+                    $level,
+                    lock.location = %lock_location,
+                    guard.location = %guard_location,
+                    guard.kind = ?guard_kind,
+                    guard.index = guard_index,
+                    duration_ns = time.as_nanos() as u64,
+                    threshold_ns = threshold.as_nanos() as u64,
+                    suppressed,
+                    "Held a {:?} guard acquired at {} for {:?}",
+                    guard_kind,
+                    guard_location,
+                    time,
+                )
+            }
+        };
+    }
+
+    // The level of an event needs to be known at compile time.
+    match level {
+        Level::ERROR => emit!(Level::ERROR),
+        Level::WARN => emit!(Level::WARN),
+        Level::INFO => emit!(Level::INFO),
+        Level::DEBUG => emit!(Level::DEBUG),
+        Level::TRACE => emit!(Level::TRACE),
+    }
+}
+
+/// A `tracing_subscriber::Layer` which allows the lock activity to be
+/// attributed to the span that is current when a guard is requested. Each
+/// span is identified by its name and the values of the selected fields, e.g.
+/// `request{endpoint="/users"}`; the related statistics are available via
+/// `GuardInfo::span_stats`.
+#[cfg(feature = "tracing_spans")]
+pub struct SpanLayer<S> {
+    fields: Vec<&'static str>,
+    get_label: WithLabel,
+    _subscriber: PhantomData<fn(S)>,
+}
+
+// Allows the label of a span to be obtained without knowing the exact type
+// of the subscriber.
+#[cfg(feature = "tracing_spans")]
+struct WithLabel(fn(&Dispatch, &Id) -> Option<Arc<str>>);
+
+// The label of a span, stored in its extensions.
+#[cfg(feature = "tracing_spans")]
+struct SpanLabel {
+    name: &'static str,
+    fields: Vec<(&'static str, String)>,
+    label: Arc<str>,
+}
+
+#[cfg(feature = "tracing_spans")]
+impl SpanLabel {
+    fn update_label(&mut self) {
+        let mut label = self.name.to_owned();
+        if !self.fields.is_empty() {
+            let fields = self
+                .fields
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            label = format!("{label}{{{fields}}}");
+        }
+        self.label = label.into();
+    }
+}
+
+#[cfg(feature = "tracing_spans")]
+impl Visit for SpanLabel {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let value = format!("{value:?}");
+        match self
+            .fields
+            .iter_mut()
+            .find(|(name, _)| *name == field.name())
+        {
+            Some((_, old_value)) => *old_value = value,
+            None => self.fields.push((field.name(), value)),
+        }
+    }
+}
+
+// Records only the selected fields of a span.
+#[cfg(feature = "tracing_spans")]
+struct SelectedFields<'a> {
+    selected: &'a [&'static str],
+    label: &'a mut SpanLabel,
+}
+
+#[cfg(feature = "tracing_spans")]
+impl Visit for SelectedFields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.selected.contains(&field.name()) {
+            self.label.record_debug(field, value);
+        }
+    }
+}
+
+#[cfg(feature = "tracing_spans")]
+impl<S> SpanLayer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    /// Creates a layer that identifies the spans by their names and the
+    /// values of the given fields.
+    pub fn new(fields: &[&'static str]) -> Self {
+        Self {
+            fields: fields.to_vec(),
+            get_label: WithLabel(Self::get_label),
+            _subscriber: PhantomData,
+        }
+    }
+
+    fn get_label(dispatch: &Dispatch, id: &Id) -> Option<Arc<str>> {
+        let subscriber = dispatch.downcast_ref::<S>()?;
+        let span = subscriber.span(id)?;
+        let extensions = span.extensions();
+        extensions
+            .get::<SpanLabel>()
+            .map(|label| label.label.clone())
+    }
+}
+
+#[cfg(feature = "tracing_spans")]
+impl<S> Layer<S> for SpanLayer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut label = SpanLabel {
+            name: attrs.metadata().name(),
+            fields: Vec::new(),
+            label: "".into(),
+        };
+        attrs.record(&mut SelectedFields {
+            selected: &self.fields,
+            label: &mut label,
+        });
+        label.update_label();
+        span.extensions_mut().insert(label);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(label) = extensions.get_mut::<SpanLabel>() {
+            values.record(&mut SelectedFields {
+                selected: &self.fields,
+                label,
+            });
+            label.update_label();
+        }
+    }
+
+    unsafe fn downcast_raw(&self, id: TypeId) -> Option<*const ()> {
+        if id == TypeId::of::<Self>() {
+            Some(self as *const Self as *const ())
+        } else if id == TypeId::of::<WithLabel>() {
+            Some(&self.get_label as *const WithLabel as *const ())
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the label of the current span, as long as a `SpanLayer` is in use.
+#[cfg(feature = "tracing_spans")]
+pub(crate) fn current_span_label() -> Option<Arc<str>> {
+    // This needs to happen outside of `get_default`, which isn't reentrant.
+    let id = Span::current().id()?;
+    tracing::dispatcher::get_default(|dispatch| {
+        let get_label = dispatch.downcast_ref::<WithLabel>()?;
+        (get_label.0)(dispatch, &id)
+    })
+}
+
+/// The statistics related to the uses of a guard within a single span.
+#[cfg(feature = "tracing_spans")]
+#[derive(Debug, Clone, Default)]
+pub struct SpanStats {
+    pub num_uses: usize,
+    pub max_wait_time: Duration,
+    pub total_wait_time: Duration,
+    pub max_duration: Duration,
+    pub total_duration: Duration,
+}
+
+#[cfg(feature = "tracing_spans")]
+impl SpanStats {
+    pub(crate) fn add_wait(&mut self, wait_time: Duration) {
+        self.num_uses += 1;
+        self.total_wait_time += wait_time;
+        if wait_time > self.max_wait_time {
+            self.max_wait_time = wait_time;
+        }
+    }
+
+    pub(crate) fn add_hold(&mut self, duration: Duration) {
+        self.total_duration += duration;
+        if duration > self.max_duration {
+            self.max_duration = duration;
+        }
+    }
+}
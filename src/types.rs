@@ -0,0 +1,161 @@
+// The plain data types shared by the tracking and the passthrough wrappers.
+
+use std::{
+    fmt,
+    path::Path,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+// The location assigned to guards that aren't tracked.
+pub(crate) static UNTRACKED_LOCATION: LazyLock<Location> = LazyLock::new(|| Location {
+    path: Path::new("<untracked>").into(),
+    line: 0,
+    col: 0,
+});
+
+/// Points to the filesystem location where a lock or guard was created.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    pub path: Arc<Path>,
+    pub line: u32,
+    pub col: u32,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}:{}", self.path.display(), self.line, self.col)
+    }
+}
+
+/// The type of the lock; either a `Mutex`, an `RwLock`, a `Semaphore` or a `Notify`.
+/// More kinds may be added along with new wrappers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LockKind {
+    Mutex,
+    RwLock,
+    Semaphore,
+    Notify,
+}
+
+/// The type of the guard that was created when working with a lock. More
+/// kinds may be added along with new wrappers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum GuardKind {
+    Lock,
+    Read,
+    Write,
+    Permit,
+    /// Waiting for a notification; such guards are never held, so their uses
+    /// only involve waiting, and they aren't released.
+    Notified,
+}
+
+/// The options that can be provided when creating a lock.
+#[derive(Debug, Clone, Default)]
+pub struct LockOptions {
+    /// The name of the lock, which can be used to filter it.
+    pub name: Option<String>,
+    /// The budget applicable to all the guards of the lock.
+    pub budget: Option<Budget>,
+}
+
+impl LockOptions {
+    /// Returns the options for a lock with the given name.
+    pub fn named<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the budget applicable to all the guards of the lock.
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+}
+
+/// The maximum wait and hold times of the guards of a lock or of a single
+/// acquisition site, along with the action taken when they are exceeded. The
+/// violations are also counted in `GuardInfo::{wait_budget_violations,
+/// hold_budget_violations}` regardless of the policy.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    /// The maximum time spent waiting to acquire a guard.
+    pub max_wait: Option<Duration>,
+    /// The maximum time a guard is held for.
+    pub max_hold: Option<Duration>,
+    /// What happens when either of the limits is exceeded.
+    pub policy: BudgetPolicy,
+}
+
+/// The action taken when a `Budget` is exceeded.
+#[derive(Clone, Default)]
+pub enum BudgetPolicy {
+    /// Calls the given function.
+    Callback(Arc<dyn Fn(&BudgetViolation) + Send + Sync>),
+    /// Writes a description of the violation to the standard error; this is the default.
+    #[default]
+    Log,
+    /// Only counts the violation.
+    Count,
+    /// Panics in builds with debug assertions (e.g. debug and test builds),
+    /// and falls back to `BudgetPolicy::Log` otherwise.
+    Panic,
+}
+
+impl fmt::Debug for BudgetPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Callback(_) => f.write_str("Callback(..)"),
+            Self::Log => f.write_str("Log"),
+            Self::Count => f.write_str("Count"),
+            Self::Panic => f.write_str("Panic"),
+        }
+    }
+}
+
+/// The limit of a `Budget` that was exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetKind {
+    Wait,
+    Hold,
+}
+
+/// Describes a single use of a guard that exceeded its `Budget`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetViolation {
+    pub kind: BudgetKind,
+    pub lock_location: Location,
+    pub guard_location: Location,
+    pub guard_kind: GuardKind,
+    pub guard_index: usize,
+    /// The time spent waiting for or holding the guard.
+    pub duration: Duration,
+    /// The limit that was exceeded.
+    pub limit: Duration,
+}
+
+impl fmt::Display for BudgetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.kind {
+            BudgetKind::Wait => "waited for",
+            BudgetKind::Hold => "held",
+        };
+
+        write!(
+            f,
+            "the {:?} guard #{} of lock {} acquired at {} was {} {:?}, exceeding its budget of {:?}",
+            self.guard_kind,
+            self.guard_index,
+            self.lock_location,
+            self.guard_location,
+            action,
+            self.duration,
+            self.limit,
+        )
+    }
+}
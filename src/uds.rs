@@ -0,0 +1,106 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    thread,
+};
+
+use crate::{
+    export::{blocked, in_use, to_text},
+    lock_info::{lock_snapshots, reset_lock_stats, set_enabled},
+    sampling::{set_sampling, Sampling},
+};
+
+/// Starts listening for commands on a Unix domain socket at the given path,
+/// using a dedicated thread, and a separate one for each connection. It
+/// doesn't use any of the locks tracked by `locktick`, so it remains
+/// responsive even if the rest of the program is deadlocked.
+///
+/// Each command is a single line, and each response is terminated by a
+/// line containing either `OK`, or `ERR` followed by a description of the
+/// error. The following commands are available:
+/// - `dump`: all the locks and their guards
+/// - `active`: the locks with guards that are currently in use
+/// - `deadlocks`: the locks that are held while others are waiting for them
+/// - `reset`: resets the statistics related to all the known guards
+/// - `enable`, `disable`: switches the accounting on or off (see `set_enabled`)
+/// - `sample N`: only tracks 1 in N acquisitions of each lock, or all of them
+///   if N is 1 (see `set_sampling`)
+pub fn start_server<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    serve(UnixListener::bind(path)?)
+}
+
+// Serves the connections accepted by the given listener on a dedicated thread.
+pub(crate) fn serve(listener: UnixListener) -> io::Result<()> {
+    thread::Builder::new()
+        .name("locktick-uds".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // Each connection is handled by its own thread, so that an idle
+                // one doesn't prevent the others from being served. There is
+                // nothing meaningful to be done about a failed response.
+                let _ = thread::Builder::new()
+                    .name("locktick-uds-conn".into())
+                    .spawn(move || handle_connection(stream));
+            }
+        })?;
+
+    Ok(())
+}
+
+// Handles the commands sent over a single connection until it is closed.
+fn handle_connection(stream: UnixStream) -> io::Result<()> {
+    let mut writer = &stream;
+    for line in BufReader::new(&stream).lines() {
+        let line = line?;
+        let mut parts = line.split_whitespace();
+        let response = match (parts.next(), parts.next(), parts.next()) {
+            (None, ..) => continue,
+            (Some(command), arg, None) => execute(command, arg),
+            _ => Err("too many arguments".into()),
+        };
+
+        match response {
+            Ok(output) => writeln!(writer, "{output}OK")?,
+            Err(e) => writeln!(writer, "ERR {e}")?,
+        }
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+// Executes a single command, returning its output.
+fn execute(command: &str, arg: Option<&str>) -> Result<String, String> {
+    let output = match (command, arg) {
+        ("dump", None) => to_text(&lock_snapshots()),
+        ("active", None) => to_text(&in_use(&lock_snapshots())),
+        ("deadlocks", None) => to_text(&blocked(&lock_snapshots())),
+        ("reset", None) => {
+            reset_lock_stats();
+            String::new()
+        }
+        ("enable", None) => {
+            set_enabled(true);
+            String::new()
+        }
+        ("disable", None) => {
+            set_enabled(false);
+            String::new()
+        }
+        ("sample", Some(n)) => match n.parse() {
+            Ok(n) if n > 0 => {
+                set_sampling(Sampling::OneIn(n));
+                String::new()
+            }
+            _ => return Err(format!("invalid sampling rate: {n}")),
+        },
+        ("dump" | "active" | "deadlocks" | "reset" | "enable" | "disable", Some(_)) => {
+            return Err(format!("{command} takes no arguments"))
+        }
+        ("sample", None) => return Err("sample requires an argument".into()),
+        _ => return Err(format!("unknown command: {command}")),
+    };
+
+    Ok(output)
+}
@@ -0,0 +1,191 @@
+// Exercises the public API of the wrappers and their guards without relying on
+// the accounting, so that it is compiled and checked both with and without the
+// `passthrough` feature, ensuring the two APIs remain the same.
+
+#[allow(dead_code)]
+fn check_guard<T>(guard: &locktick::LockGuard<T>) {
+    let _: bool = guard.is_tracked();
+    let _: &locktick::Location = guard.lock_location();
+    let _: &locktick::Location = guard.guard_location();
+    let _: usize = guard.guard_index();
+}
+
+#[cfg(feature = "std")]
+mod std_api {
+    use std::{sync::WaitTimeoutResult, time::Duration};
+
+    use locktick::{std::*, LockOptions};
+
+    use super::check_guard;
+
+    #[test]
+    fn std_api() {
+        let mutex = Mutex::with_options(0u8, LockOptions::named("mutex"));
+        let mut guard = mutex.lock().unwrap();
+        check_guard(&guard);
+        *guard += 1;
+        assert!(mutex.try_lock().is_err());
+
+        let condvar = Condvar::new();
+        condvar.notify_one();
+        condvar.notify_all();
+        let (guard, result): (_, WaitTimeoutResult) =
+            condvar.wait_timeout(guard, Duration::ZERO).unwrap();
+        assert!(result.timed_out());
+        let guard = condvar.wait_while(guard, |value| *value == 0).unwrap();
+        let (guard, result): (_, WaitTimeoutResult) = condvar
+            .wait_timeout_while(guard, Duration::ZERO, |value| *value == 0)
+            .unwrap();
+        assert!(!result.timed_out());
+        drop(guard);
+        let _ = format!("{:?} {:?}", Mutex::<u8>::default(), Condvar::default());
+
+        let rwlock = RwLock::new(0u8);
+        let read = rwlock.read().unwrap();
+        check_guard(&read);
+        assert!(rwlock.try_read().is_ok());
+        assert!(rwlock.try_write().is_err());
+        drop(read);
+        *rwlock.write().unwrap() += 1;
+        let _ = format!("{:?}", RwLock::<u8>::default());
+    }
+}
+
+#[cfg(feature = "parking_lot")]
+mod parking_lot_api {
+    use std::time::{Duration, Instant};
+
+    use locktick::{parking_lot::*, LockOptions};
+
+    use super::check_guard;
+
+    #[test]
+    fn parking_lot_api() {
+        let mutex = Mutex::with_options(0u8, LockOptions::named("mutex"));
+        let mut guard = mutex.lock();
+        check_guard(&guard);
+        *guard += 1;
+        assert!(mutex.try_lock().is_none());
+
+        let condvar = Condvar::new();
+        let _: bool = condvar.notify_one();
+        let _: usize = condvar.notify_all();
+        assert!(condvar.wait_for(&mut guard, Duration::ZERO).timed_out());
+        assert!(condvar.wait_until(&mut guard, Instant::now()).timed_out());
+        condvar.wait_while(&mut guard, |value| *value == 0);
+        drop(guard);
+        let _ = format!("{:?} {:?}", Mutex::<u8>::default(), Condvar::default());
+
+        let rwlock = RwLock::new(0u8);
+        let read = rwlock.read();
+        check_guard(&read);
+        assert!(rwlock.try_read().is_some());
+        assert!(rwlock.try_write().is_none());
+        drop(read);
+        *rwlock.write() += 1;
+        assert_eq!(rwlock.into_inner(), 1);
+        let _ = format!("{:?}", RwLock::<u8>::default());
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_api {
+    use std::{
+        pin::{pin, Pin},
+        sync::Arc,
+    };
+
+    use locktick::{tokio::*, LockGuard, LockOptions};
+
+    use super::check_guard;
+
+    #[tokio::test]
+    async fn tokio_api() {
+        let mutex = Arc::new(Mutex::with_options(0u8, LockOptions::named("mutex")));
+        let mut guard = mutex.lock().await;
+        check_guard(&guard);
+        *guard += 1;
+        assert!(mutex.try_lock().is_err());
+        drop(guard);
+        let mut guard: LockGuard<OwnedMutexGuard<u8>> = mutex.clone().lock_owned().await;
+        *guard += 1;
+        assert!(mutex.clone().try_lock_owned().is_err());
+        let guard: OwnedMutexGuard<u8> = guard.into_inner();
+        assert!(Arc::ptr_eq(OwnedMutexGuard::mutex(&guard), &mutex));
+        let mut mapped: OwnedMappedMutexGuard<u8, u8> = OwnedMutexGuard::map(guard, |value| value);
+        *mapped += 1;
+        assert!(Arc::ptr_eq(OwnedMappedMutexGuard::mutex(&mapped), &mutex));
+        drop(mapped);
+        let _ = format!("{:?}", Mutex::<u8>::default());
+
+        let rwlock = Arc::new(RwLock::new(0u8));
+        let read = rwlock.read().await;
+        check_guard(&read);
+        assert!(rwlock.try_read().is_ok());
+        assert!(rwlock.try_write().is_err());
+        drop(read);
+        *rwlock.write().await += 1;
+        let read: LockGuard<OwnedRwLockReadGuard<u8>> = rwlock.clone().read_owned().await;
+        assert!(rwlock.clone().try_read_owned().is_ok());
+        assert!(rwlock.clone().try_write_owned().is_err());
+        drop(read);
+        let mut write: LockGuard<OwnedRwLockWriteGuard<u8>> = rwlock.clone().write_owned().await;
+        *write += 1;
+        let write: OwnedRwLockWriteGuard<u8> = write.into_inner();
+        assert!(Arc::ptr_eq(OwnedRwLockWriteGuard::rwlock(&write), &rwlock));
+        let mut mapped: OwnedRwLockMappedWriteGuard<u8, u8> =
+            OwnedRwLockWriteGuard::map(write, |value| value);
+        *mapped += 1;
+        assert!(Arc::ptr_eq(
+            OwnedRwLockMappedWriteGuard::rwlock(&mapped),
+            &rwlock
+        ));
+        drop(mapped);
+        let write = rwlock.clone().write_owned().await.into_inner();
+        let read: OwnedRwLockReadGuard<u8> = write.downgrade();
+        assert!(rwlock.clone().try_read_owned().is_ok());
+        assert!(rwlock.clone().try_write_owned().is_err());
+        let read: OwnedRwLockReadGuard<u8, u8> = OwnedRwLockReadGuard::map(read, |value| value);
+        assert!(Arc::ptr_eq(OwnedRwLockReadGuard::rwlock(&read), &rwlock));
+        drop(read);
+        let rwlock = Arc::try_unwrap(rwlock).ok().unwrap();
+        assert_eq!(rwlock.into_inner(), 3);
+        let _ = format!("{:?}", RwLock::<u8>::default());
+
+        let semaphore = Arc::new(Semaphore::with_options(2, LockOptions::named("semaphore")));
+        let permit = semaphore.acquire().await.unwrap();
+        check_guard(&permit);
+        assert!(semaphore.try_acquire_many(2).is_err());
+        drop(permit);
+        drop(semaphore.acquire_many(2).await.unwrap());
+        drop(semaphore.try_acquire().unwrap());
+        drop(semaphore.clone().acquire_owned().await.unwrap());
+        drop(semaphore.clone().acquire_many_owned(2).await.unwrap());
+        drop(semaphore.clone().try_acquire_owned().unwrap());
+        drop(semaphore.clone().try_acquire_many_owned(2).unwrap());
+        let permit: tokio::sync::OwnedSemaphorePermit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .unwrap()
+            .into_inner();
+        permit.forget();
+        semaphore.add_permits(2);
+        assert_eq!(semaphore.available_permits(), 3);
+        semaphore.close();
+        assert!(semaphore.is_closed());
+        let _ = format!("{semaphore:?}");
+
+        let notify = Notify::with_options(LockOptions::named("notify"));
+        let notified = notify.notified();
+        notify.notify_waiters();
+        notified.await;
+        notify.notify_one();
+        notify.notified().await;
+        let mut notified: Pin<&mut Notified<'_>> = pin!(notify.notified());
+        notified.as_mut().enable();
+        notify.notify_waiters();
+        notified.await;
+        let _ = format!("{:?}", Notify::default());
+    }
+}
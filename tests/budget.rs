@@ -0,0 +1,120 @@
+mod common;
+
+#[cfg(all(feature = "std", not(feature = "passthrough")))]
+mod tests {
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        sync::{Arc, Mutex as StdMutex},
+        thread,
+        time::Duration,
+    };
+
+    use locktick::{
+        clear_lock_infos, lock_snapshots, set_site_budget, std::*, Budget, BudgetKind,
+        BudgetPolicy, BudgetViolation, LockOptions,
+    };
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    #[test]
+    #[serial]
+    fn lock_budget() {
+        clear_lock_infos();
+
+        let violations = Arc::new(StdMutex::new(Vec::<BudgetViolation>::new()));
+        let recorder = violations.clone();
+        let budget = Budget {
+            max_hold: Some(Duration::from_millis(20)),
+            policy: BudgetPolicy::Callback(Arc::new(move |violation| {
+                recorder.lock().unwrap().push(violation.clone());
+            })),
+            ..Default::default()
+        };
+        let lock = Mutex::with_options(Object, LockOptions::default().with_budget(budget));
+
+        drop(lock.lock().unwrap());
+        assert!(violations.lock().unwrap().is_empty());
+
+        let guard = lock.lock().unwrap();
+        let (guard_location, guard_index) = (guard.guard_location().clone(), guard.guard_index());
+        thread::sleep(Duration::from_millis(30));
+        drop(guard);
+
+        let violations = violations.lock().unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, BudgetKind::Hold);
+        assert_eq!(violations[0].guard_index, guard_index);
+        assert!(violations[0].duration > violations[0].limit);
+
+        let locks = lock_snapshots();
+        let guard = &locks[0].known_guards[&guard_location];
+        assert_eq!(guard.hold_budget_violations, 1);
+        assert_eq!(guard.wait_budget_violations, 0);
+    }
+
+    #[test]
+    #[serial]
+    fn site_budget() {
+        clear_lock_infos();
+
+        let lock = RwLock::new(Object);
+        // All the read guards are acquired at the same site.
+        let read = || lock.read().unwrap();
+        let site = read().guard_location().clone();
+        set_site_budget(
+            site.clone(),
+            Some(Budget {
+                max_wait: Some(Duration::from_millis(20)),
+                policy: BudgetPolicy::Count,
+                ..Default::default()
+            }),
+        );
+
+        let write = lock.write().unwrap();
+        thread::scope(|s| {
+            s.spawn(|| drop(read()));
+            thread::sleep(Duration::from_millis(50));
+            drop(write);
+        });
+        let locks = lock_snapshots();
+        assert_eq!(locks[0].known_guards[&site].wait_budget_violations, 1);
+
+        // The waits at other sites aren't subject to the budget.
+        let guard = read();
+        let write_site = thread::scope(|s| {
+            let writer = s.spawn(|| lock.write().unwrap().guard_location().clone());
+            thread::sleep(Duration::from_millis(50));
+            drop(guard);
+            writer.join().unwrap()
+        });
+        let locks = lock_snapshots();
+        assert_eq!(locks[0].known_guards[&write_site].wait_budget_violations, 0);
+
+        set_site_budget(site, None);
+    }
+
+    #[test]
+    #[serial]
+    fn panic_policy() {
+        clear_lock_infos();
+
+        let budget = Budget {
+            max_hold: Some(Duration::ZERO),
+            policy: BudgetPolicy::Panic,
+            ..Default::default()
+        };
+        let lock = Mutex::with_options(Object, LockOptions::default().with_budget(budget));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| drop(lock.lock().unwrap())));
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("exceeding its budget of 0ns"));
+        check_locks!(1, 1, 0);
+
+        // The guard was released before the panic, so the lock isn't poisoned,
+        // and the next guard panics upon being dropped as well.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| lock.lock().is_ok()));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,51 @@
+mod common;
+
+#[cfg(all(
+    feature = "std",
+    feature = "chrome_trace",
+    not(feature = "passthrough")
+))]
+mod tests {
+    use locktick::{chrome_trace::*, clear_lock_infos, std::*};
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    #[test]
+    #[serial]
+    fn recording() {
+        clear_lock_infos();
+
+        let lock = Mutex::new(Object);
+        start_recording();
+
+        contend(|| lock.lock().unwrap());
+
+        stop_recording();
+        let _ignored = lock.lock().unwrap();
+
+        let mut trace = Vec::new();
+        write_chrome_trace(&mut trace).unwrap();
+        let trace: serde_json::Value = serde_json::from_slice(&trace).unwrap();
+        let events = trace.as_array().unwrap();
+
+        let complete = |cat: &str| {
+            events
+                .iter()
+                .filter(|e| e["ph"] == "X" && e["cat"] == cat)
+                .collect::<Vec<_>>()
+        };
+        let holds = complete("hold");
+        assert_eq!(holds.len(), 2);
+        let waits = complete("wait");
+        assert_eq!(waits.len(), 1);
+        assert!(waits[0]["dur"].as_f64().unwrap() > 0.0);
+        assert_eq!(waits[0]["args"]["kind"], "Lock");
+
+        // Each of the 2 threads has its own identifier and name.
+        let threads = events.iter().filter(|e| e["ph"] == "M").count();
+        assert_eq!(threads, 2);
+        assert_ne!(waits[0]["tid"], holds[0]["tid"]);
+    }
+}
@@ -21,8 +21,8 @@ macro_rules! check_locks {
 macro_rules! check_guard {
     ($guard:expr, $uses:expr, $active:expr) => {{
         {
-            let lock_location = &$guard.lock_location;
-            let guard_location = &$guard.guard_location;
+            let lock_location = $guard.lock_location();
+            let guard_location = $guard.guard_location();
             assert_eq!(guard_location.line, line!() - 1);
             let locks = lock_snapshots();
             let lock = locks.iter().find(|l| l.location == *lock_location).unwrap();
@@ -35,3 +35,15 @@ macro_rules! check_guard {
 
 #[allow(unused)]
 pub struct Object;
+
+/// Makes another thread wait for a guard for a while; the guard is obtained
+/// via `acquire` on the current thread first, and then on the other one.
+#[allow(unused)]
+pub fn contend<G>(acquire: impl Fn() -> G + Sync) {
+    std::thread::scope(|s| {
+        let guard = acquire();
+        s.spawn(|| drop(acquire()));
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        drop(guard);
+    });
+}
@@ -0,0 +1,146 @@
+mod common;
+
+#[cfg(all(feature = "std", not(feature = "passthrough")))]
+mod tests {
+    use std::{
+        env,
+        process::{Command, Output},
+        time::Duration,
+    };
+
+    use locktick::{lock_snapshots, std::*, Config, ConfigError, LockKind, Sampling};
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    // The environment variable used to only run the `configured_process` test
+    // in a dedicated child process, and to select the expected configuration.
+    const CHILD_VAR: &str = "LOCKTICK_CONFIG_CHILD";
+
+    #[test]
+    #[serial]
+    fn configured_process() {
+        let Some(mode) = env::var_os(CHILD_VAR) else {
+            return;
+        };
+
+        let lock = Mutex::new(Object);
+        let tracked = (0..4).filter(|_| lock.lock().unwrap().is_tracked()).count();
+
+        match mode.to_str().unwrap() {
+            "sampling" => {
+                assert_eq!(tracked, 2);
+                check_locks!(1, 1, 0);
+            }
+            "excluded" => {
+                assert_eq!(tracked, 0);
+                check_locks!(0, 0, 0);
+            }
+            "invalid" => {
+                assert_eq!(tracked, 4);
+                check_locks!(1, 1, 0);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn run_child(mode: &str, var: &str, value: &str) -> Output {
+        let output = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "tests::configured_process", "--test-threads=1"])
+            .env(CHILD_VAR, mode)
+            .env(var, value)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        output
+    }
+
+    #[test]
+    #[serial]
+    fn env_config() {
+        run_child("sampling", "LOCKTICK_SAMPLING", "2");
+        run_child("excluded", "LOCKTICK_INCLUDE", "/nonexistent/");
+
+        let output = run_child("invalid", "LOCKTICK_SAMPLING", "often");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(
+            "locktick: the environment configuration wasn't applied: invalid value of LOCKTICK_SAMPLING (\"often\")"
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn env_precedence() {
+        env::set_var("LOCKTICK_SAMPLING", "2");
+        let mut config = Config::default();
+        config.sampling = Sampling::OneIn(3);
+        config.apply().unwrap();
+        assert_eq!(Config::current().sampling, Sampling::OneIn(2));
+
+        env::remove_var("LOCKTICK_SAMPLING");
+        Config::default().apply().unwrap();
+        assert_eq!(Config::current().sampling, Sampling::All);
+    }
+
+    #[test]
+    #[serial]
+    fn parsing() {
+        env::set_var("LOCKTICK_ENABLED", "0");
+        env::set_var("LOCKTICK_SAMPLING", "1.5ms");
+        env::set_var(
+            "LOCKTICK_EXCLUDE",
+            "/a, /b/c, module:a::b, name: cache, kind:rwlock",
+        );
+        let config = Config::from_env().unwrap();
+        let mut expected = Config::default();
+        expected.enabled = false;
+        expected.sampling = Sampling::Interval(Duration::from_micros(1500));
+        expected.filters = locktick::Filters {
+            exclude_paths: vec!["/a".into(), "/b/c".into()],
+            exclude_modules: vec!["a::b".into()],
+            exclude_names: vec!["cache".into()],
+            exclude_kinds: vec![LockKind::RwLock],
+            ..Default::default()
+        };
+        assert_eq!(config, expected);
+
+        env::set_var("LOCKTICK_SAMPLING", "10 days");
+        let error = Config::from_env().unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::InvalidValue {
+                var: "LOCKTICK_SAMPLING",
+                ..
+            }
+        ));
+
+        for var in ["LOCKTICK_ENABLED", "LOCKTICK_SAMPLING", "LOCKTICK_EXCLUDE"] {
+            env::remove_var(var);
+        }
+
+        if cfg!(not(feature = "tracing")) {
+            env::set_var("LOCKTICK_THRESHOLD_LEVEL", "info");
+            assert!(matches!(
+                Config::from_env(),
+                Err(ConfigError::MissingFeature {
+                    feature: "tracing",
+                    ..
+                })
+            ));
+            env::remove_var("LOCKTICK_THRESHOLD_LEVEL");
+        }
+
+        let mut config = Config::default();
+        config.http_addr = Some("127.0.0.1:0".into());
+        if cfg!(not(feature = "http")) {
+            assert!(matches!(
+                config.apply(),
+                Err(ConfigError::MissingFeature {
+                    feature: "http",
+                    ..
+                })
+            ));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+mod common;
+
+#[cfg(all(feature = "std", feature = "http", not(feature = "passthrough")))]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{SocketAddr, TcpStream},
+    };
+
+    use locktick::{clear_lock_infos, http::start_server, lock_snapshots, std::*};
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    fn request(addr: SocketAddr, method: &str, path: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.lines().next().unwrap().to_owned();
+        (status, body.to_owned())
+    }
+
+    #[test]
+    #[serial]
+    fn routes() {
+        clear_lock_infos();
+        let addr = start_server("127.0.0.1:0").unwrap();
+
+        let lock1 = Mutex::new(Object);
+        let lock2 = RwLock::new(Object);
+        let _guard = lock1.lock().unwrap();
+        drop(lock2.read().unwrap());
+
+        let (status, body) = request(addr, "GET", "/locks");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body.lines().filter(|l| !l.starts_with('-')).count(), 2);
+
+        let (_, body) = request(addr, "GET", "/locks.json");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+
+        let (_, body) = request(addr, "GET", "/active.json");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(json[0]["kind"], "Mutex");
+        assert_eq!(json[0]["guards"][0]["active_uses"], 1);
+
+        let (_, body) = request(addr, "GET", "/metrics");
+        assert!(body.contains("# TYPE locktick_guard_uses_total counter"));
+        assert_eq!(
+            body.lines()
+                .filter(|l| l.starts_with("locktick_guard_active{"))
+                .count(),
+            2
+        );
+
+        let (_, body) = request(addr, "GET", "/deadlocks");
+        assert!(body.is_empty());
+
+        let (status, _) = request(addr, "GET", "/reset");
+        assert_eq!(status, "HTTP/1.1 405 Method Not Allowed");
+        let (status, _) = request(addr, "GET", "/unknown");
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+    }
+
+    #[test]
+    #[serial]
+    fn reset() {
+        clear_lock_infos();
+        let addr = start_server("127.0.0.1:0").unwrap();
+
+        let lock = Mutex::new(Object);
+        drop(lock.lock().unwrap());
+        let guard = lock.lock().unwrap();
+
+        let (status, _) = request(addr, "POST", "/reset");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+
+        let locks = lock_snapshots();
+        let guards = locks[0].known_guards.values().collect::<Vec<_>>();
+        assert!(guards.iter().all(|g| g.num_uses == 0));
+        assert_eq!(guards.iter().map(|g| g.num_active_uses()).sum::<usize>(), 1);
+
+        // The active guard can still be dropped correctly.
+        drop(guard);
+        check_locks!(1, 2, 0);
+    }
+}
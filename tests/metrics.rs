@@ -0,0 +1,65 @@
+mod common;
+
+#[cfg(all(feature = "std", feature = "metrics", not(feature = "passthrough")))]
+mod tests {
+    use locktick::{clear_lock_infos, std::*};
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    #[test]
+    #[serial]
+    fn recorder() {
+        clear_lock_infos();
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let lock = RwLock::new(Object);
+        let _write = metrics::with_local_recorder(&recorder, || {
+            let read = lock.read().unwrap();
+            drop(read);
+            let write = lock.write().unwrap();
+            drop(write);
+            lock.write().unwrap()
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let counter_sum = |name: &str| {
+            snapshot
+                .iter()
+                .filter(|(key, ..)| key.key().name() == name)
+                .map(|(.., value)| match value {
+                    DebugValue::Counter(value) => *value,
+                    _ => panic!("unexpected value type"),
+                })
+                .sum::<u64>()
+        };
+        let histogram_len = |name: &str| {
+            snapshot
+                .iter()
+                .filter(|(key, ..)| key.key().name() == name)
+                .map(|(.., value)| match value {
+                    DebugValue::Histogram(values) => values.len(),
+                    _ => panic!("unexpected value type"),
+                })
+                .sum::<usize>()
+        };
+
+        assert_eq!(counter_sum("locktick.guard.acquired"), 3);
+        assert_eq!(counter_sum("locktick.guard.released"), 2);
+        assert_eq!(histogram_len("locktick.guard.wait_time"), 3);
+        assert_eq!(histogram_len("locktick.guard.duration"), 2);
+
+        let (key, ..) = snapshot
+            .iter()
+            .find(|(key, ..)| key.key().name() == "locktick.guard.acquired")
+            .unwrap();
+        assert!(key
+            .key()
+            .labels()
+            .any(|label| label.key() == "lock.kind" && label.value() == "RwLock"));
+    }
+}
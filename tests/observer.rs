@@ -0,0 +1,182 @@
+mod common;
+
+#[cfg(all(feature = "std", not(feature = "passthrough")))]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex as StdMutex},
+        thread,
+        time::Duration,
+    };
+
+    use locktick::{
+        add_observer, clear_lock_infos, clear_observers, std::*, GuardKind, Location, LockKind,
+        LockObserver,
+    };
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Event {
+        Registered(LockKind),
+        WaitStarted(usize),
+        WaitCancelled(usize),
+        Acquired(usize),
+        TryLockFailed,
+        Released(usize),
+    }
+
+    // Records the events related to a single lock.
+    #[derive(Default)]
+    struct Recorder(StdMutex<Vec<Event>>);
+
+    impl LockObserver for Recorder {
+        fn lock_registered(&self, _lock_location: &Location, lock_kind: LockKind) {
+            self.0.lock().unwrap().push(Event::Registered(lock_kind));
+        }
+
+        fn wait_started(
+            &self,
+            _lock_location: &Location,
+            _guard_location: &Location,
+            guard_kind: GuardKind,
+            wait_index: usize,
+        ) {
+            assert_eq!(guard_kind, GuardKind::Lock);
+            self.0.lock().unwrap().push(Event::WaitStarted(wait_index));
+        }
+
+        fn wait_cancelled(
+            &self,
+            _lock_location: &Location,
+            _guard_location: &Location,
+            _guard_kind: GuardKind,
+            wait_index: usize,
+            _wait_time: Duration,
+        ) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(Event::WaitCancelled(wait_index));
+        }
+
+        fn guard_acquired(
+            &self,
+            _lock_location: &Location,
+            _guard_location: &Location,
+            _guard_kind: GuardKind,
+            guard_index: usize,
+            _wait_time: Duration,
+        ) {
+            self.0.lock().unwrap().push(Event::Acquired(guard_index));
+        }
+
+        fn try_lock_failed(
+            &self,
+            _lock_location: &Location,
+            _guard_location: &Location,
+            _guard_kind: GuardKind,
+        ) {
+            self.0.lock().unwrap().push(Event::TryLockFailed);
+        }
+
+        fn guard_released(
+            &self,
+            _lock_location: &Location,
+            _guard_location: &Location,
+            _guard_kind: GuardKind,
+            guard_index: usize,
+            duration: Duration,
+        ) {
+            assert!(duration > Duration::ZERO);
+            self.0.lock().unwrap().push(Event::Released(guard_index));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn lifecycle() {
+        clear_lock_infos();
+
+        let recorder = Arc::new(Recorder::default());
+        add_observer(recorder.clone());
+
+        let lock = Mutex::new(Object);
+        let guard = lock.lock().unwrap();
+        let first_idx = guard.guard_index();
+        thread::scope(|s| {
+            s.spawn(|| {
+                assert!(lock.try_lock().is_err());
+                let _guard = lock.lock().unwrap();
+            });
+            thread::sleep(Duration::from_millis(50));
+            drop(guard);
+        });
+
+        clear_observers();
+        // The observers are no longer notified once they are unregistered.
+        drop(lock.lock().unwrap());
+
+        let events = recorder.0.lock().unwrap();
+        let Event::WaitStarted(second_idx) = events[3] else {
+            panic!("unexpected events: {events:?}");
+        };
+        assert_eq!(
+            *events,
+            [
+                Event::Registered(LockKind::Mutex),
+                Event::Acquired(first_idx),
+                Event::TryLockFailed,
+                Event::WaitStarted(second_idx),
+                Event::Released(first_idx),
+                Event::Acquired(second_idx),
+                Event::Released(second_idx),
+            ]
+        );
+    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    #[serial]
+    async fn cancelled_wait() {
+        use std::{future::poll_fn, future::Future, task::Poll};
+
+        clear_lock_infos();
+
+        let recorder = Arc::new(Recorder::default());
+        add_observer(recorder.clone());
+
+        // Locks created at the same location are only reported once.
+        let locks = (0..2)
+            .map(|_| locktick::tokio::Mutex::new(Object))
+            .collect::<Vec<_>>();
+        let guard = locks[0].lock().await;
+        let first_idx = guard.guard_index();
+        let mut future = Box::pin(locks[0].lock());
+        poll_fn(|cx| {
+            assert!(future.as_mut().poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        // Dropping the pending future cancels the wait.
+        drop(future);
+        drop(guard);
+
+        clear_observers();
+
+        let events = recorder.0.lock().unwrap();
+        let Event::WaitStarted(second_idx) = events[2] else {
+            panic!("unexpected events: {events:?}");
+        };
+        assert_eq!(
+            *events,
+            [
+                Event::Registered(LockKind::Mutex),
+                Event::Acquired(first_idx),
+                Event::WaitStarted(second_idx),
+                Event::WaitCancelled(second_idx),
+                Event::Released(first_idx),
+            ]
+        );
+    }
+}
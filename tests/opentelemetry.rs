@@ -0,0 +1,89 @@
+mod common;
+
+#[cfg(all(
+    feature = "std",
+    feature = "opentelemetry",
+    not(feature = "passthrough")
+))]
+mod tests {
+    use locktick::{
+        clear_lock_infos, opentelemetry::register_instruments, reset_lock_stats, std::*,
+    };
+    use opentelemetry::metrics::MeterProvider;
+    use opentelemetry_sdk::metrics::{
+        data::{AggregatedMetrics, MetricData},
+        InMemoryMetricExporter, PeriodicReader, SdkMeterProvider,
+    };
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    #[test]
+    #[serial]
+    fn instruments() {
+        clear_lock_infos();
+
+        let exporter = InMemoryMetricExporter::default();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(exporter.clone()).build())
+            .build();
+        register_instruments(&provider.meter("locktick"));
+
+        let lock = Mutex::new(Object);
+        let guard1 = lock.lock().unwrap();
+        drop(guard1);
+        let guard2 = lock.lock().unwrap();
+
+        provider.force_flush().unwrap();
+        let metrics = exporter.get_finished_metrics().unwrap();
+        let metrics = metrics
+            .iter()
+            .flat_map(|rm| rm.scope_metrics())
+            .flat_map(|sm| sm.metrics())
+            .collect::<Vec<_>>();
+
+        let uses = metrics
+            .iter()
+            .find(|m| m.name() == "locktick.guard.uses")
+            .unwrap();
+        let AggregatedMetrics::U64(MetricData::Sum(uses)) = uses.data() else {
+            panic!("unexpected data type");
+        };
+        let uses = uses.data_points().collect::<Vec<_>>();
+        assert_eq!(uses.len(), 2);
+        assert!(uses.iter().all(|dp| dp.value() == 1));
+        assert!(uses.iter().all(|dp| dp
+            .attributes()
+            .any(|kv| kv.key.as_str() == "lock.kind" && kv.value.as_str() == "Mutex")));
+
+        let active = metrics
+            .iter()
+            .find(|m| m.name() == "locktick.guard.active")
+            .unwrap();
+        let AggregatedMetrics::U64(MetricData::Gauge(active)) = active.data() else {
+            panic!("unexpected data type");
+        };
+        let num_active = active.data_points().map(|dp| dp.value()).sum::<u64>();
+        assert_eq!(num_active, 1);
+
+        // The uses remain monotonic after the statistics are reset.
+        drop(guard2);
+        reset_lock_stats();
+        drop(lock.lock().unwrap());
+        exporter.reset();
+        provider.force_flush().unwrap();
+        let metrics = exporter.get_finished_metrics().unwrap();
+        let uses = metrics
+            .iter()
+            .flat_map(|rm| rm.scope_metrics())
+            .flat_map(|sm| sm.metrics())
+            .find(|m| m.name() == "locktick.guard.uses")
+            .unwrap();
+        let AggregatedMetrics::U64(MetricData::Sum(uses)) = uses.data() else {
+            panic!("unexpected data type");
+        };
+        let total = uses.data_points().map(|dp| dp.value()).sum::<u64>();
+        assert_eq!(total, 3);
+    }
+}
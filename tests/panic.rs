@@ -0,0 +1,103 @@
+mod common;
+
+#[cfg(all(feature = "std", not(feature = "passthrough")))]
+mod tests {
+    use std::{env, io::Write, panic, process::Command, thread};
+
+    use locktick::{clear_lock_infos, install_panic_hook, lock_snapshots, std::*};
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    // The environment variable used to only run the `panicking_process` test
+    // in a dedicated child process.
+    const CHILD_VAR: &str = "LOCKTICK_PANIC_CHILD";
+
+    #[test]
+    #[serial]
+    fn panicking_process() {
+        if env::var_os(CHILD_VAR).is_none() {
+            return;
+        }
+        clear_lock_infos();
+
+        panic::set_hook(Box::new(|_| {
+            let _ = writeln!(std::io::stderr(), "the previous hook was called");
+        }));
+        install_panic_hook();
+
+        let held = Mutex::new(Object);
+        let panicking = Mutex::new(Object);
+        let _guard = held.lock().unwrap();
+        let result = thread::scope(|s| {
+            thread::Builder::new()
+                .name("panicking".into())
+                .spawn_scoped(s, || {
+                    let _guard = panicking.lock().unwrap();
+                    panic!("intentional");
+                })
+                .unwrap()
+                .join()
+        });
+
+        assert!(result.is_err());
+        // The guard was dropped during unwinding.
+        check_locks!(2, 2, 1);
+    }
+
+    #[test]
+    fn hook() {
+        let output = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "tests::panicking_process", "--test-threads=1"])
+            .env(CHILD_VAR, "1")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let (previous, dump) = stderr
+            .split_once("locktick: the state of the locks when thread 'panicking' panicked:\n")
+            .unwrap();
+        assert!(previous.contains("the previous hook was called"));
+        // Both guards are listed, and only the one of the panicking thread is marked.
+        let held = dump
+            .lines()
+            .filter(|line| line.contains(" held for "))
+            .collect::<Vec<_>>();
+        assert_eq!(held.len(), 2);
+        assert_eq!(
+            held.iter()
+                .filter(|line| line.ends_with(" (this thread)"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn held_by() {
+        clear_lock_infos();
+
+        let lock = RwLock::new(Object);
+        let guard = lock.read().unwrap();
+        let this_thread = thread::current().id();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                let _guard = lock.read().unwrap();
+                let other_thread = thread::current().id();
+
+                let locks = lock_snapshots();
+                let guards = locks[0].known_guards.values().collect::<Vec<_>>();
+                let held_here = guards.iter().map(|g| g.held_by(other_thread).len());
+                assert_eq!(held_here.sum::<usize>(), 1);
+                let held_there = guards.iter().map(|g| g.held_by(this_thread).len());
+                assert_eq!(held_there.sum::<usize>(), 1);
+            });
+        });
+
+        drop(guard);
+        check_locks!(1, 2, 0);
+    }
+}
@@ -1,8 +1,13 @@
 mod common;
 
-#[cfg(feature = "parking_lot")]
+#[cfg(all(feature = "parking_lot", not(feature = "passthrough")))]
 mod tests {
-    use locktick::{clear_lock_infos, lock_snapshots, parking_lot::*};
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use locktick::{clear_lock_infos, condvar_snapshots, lock_snapshots, parking_lot::*};
     use serial_test::serial;
 
     use super::*;
@@ -57,4 +62,54 @@ mod tests {
         let _lock2 = RwLock::new(Object);
         check_locks!(2, 3, 0);
     }
+
+    #[test]
+    #[serial]
+    fn condvar() {
+        clear_lock_infos();
+
+        let lock = Mutex::new(false);
+        let condvar = Condvar::new();
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                *lock.lock() = true;
+                condvar.notify_all();
+            });
+
+            let mut guard = lock.lock();
+            let first_idx = guard.guard_index();
+            condvar.wait_while(&mut guard, |ready| !*ready);
+            assert!(*guard);
+            // The guard is reacquired as a new use of the same site.
+            assert_ne!(guard.guard_index(), first_idx);
+            check_locks!(1, 2, 1);
+        });
+
+        let locks = lock_snapshots();
+        let waiter = locks[0]
+            .known_guards
+            .values()
+            .find(|guard| guard.num_uses >= 2)
+            .unwrap();
+        // The guard isn't considered held while waiting on the condvar.
+        assert!(waiter.max_duration < Duration::from_millis(50));
+
+        let mut guard = lock.lock();
+        assert!(condvar
+            .wait_for(&mut guard, Duration::from_millis(10))
+            .timed_out());
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert!(condvar.wait_until(&mut guard, deadline).timed_out());
+        assert!(guard.is_tracked());
+        drop(guard);
+        check_locks!(1, 3, 0);
+
+        let condvars = condvar_snapshots();
+        assert_eq!(condvars.len(), 1);
+        assert_eq!(condvars[0].num_notify_all, 1);
+        assert!(condvars[0].num_wakeups >= 1);
+        assert_eq!(condvars[0].num_timeouts, 2);
+        assert_eq!(condvars[0].num_waiting(), 0);
+    }
 }
@@ -0,0 +1,52 @@
+mod common;
+
+#[cfg(all(feature = "std", feature = "passthrough"))]
+mod tests {
+    use std::mem::size_of;
+
+    use locktick::{std::*, LockGuard};
+
+    use super::*;
+    use common::*;
+
+    #[test]
+    fn passthrough() {
+        let lock1 = Mutex::new(0);
+        let lock2 = RwLock::new(Object);
+
+        let mut guard = lock1.lock().unwrap();
+        *guard += 1;
+        assert!(!guard.is_tracked());
+        assert!(lock1.try_lock().is_err());
+        drop(guard);
+        assert_eq!(*lock1.try_lock().unwrap(), 1);
+
+        let _read1 = lock2.read().unwrap();
+        let _read2 = lock2.try_read().unwrap();
+        assert!(lock2.try_write().is_err());
+
+        // The wrappers add no overhead in terms of size.
+        assert_eq!(size_of::<Mutex<u8>>(), size_of::<std::sync::Mutex<u8>>());
+        assert_eq!(
+            size_of::<LockGuard<std::sync::MutexGuard<'_, u8>>>(),
+            size_of::<std::sync::MutexGuard<'_, u8>>()
+        );
+        #[cfg(feature = "tokio")]
+        {
+            use locktick::tokio;
+
+            assert_eq!(
+                size_of::<tokio::Mutex<u8>>(),
+                size_of::<::tokio::sync::Mutex<u8>>()
+            );
+            assert_eq!(
+                size_of::<tokio::RwLock<u8>>(),
+                size_of::<::tokio::sync::RwLock<u8>>()
+            );
+            assert_eq!(
+                size_of::<LockGuard<::tokio::sync::MutexGuard<'_, u8>>>(),
+                size_of::<::tokio::sync::MutexGuard<'_, u8>>()
+            );
+        }
+    }
+}
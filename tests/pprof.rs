@@ -0,0 +1,60 @@
+mod common;
+
+#[cfg(all(feature = "std", feature = "pprof", not(feature = "passthrough")))]
+mod tests {
+    use locktick::{clear_lock_infos, pprof::write_pprof, stacks::*, std::*};
+    use prost::Message;
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    #[derive(Clone, PartialEq, Message)]
+    struct Profile {
+        #[prost(message, repeated, tag = "2")]
+        sample: Vec<Sample>,
+        #[prost(string, repeated, tag = "6")]
+        string_table: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    struct Sample {
+        #[prost(uint64, repeated, tag = "1")]
+        location_id: Vec<u64>,
+        #[prost(int64, repeated, tag = "2")]
+        value: Vec<i64>,
+    }
+
+    #[test]
+    #[serial]
+    fn profile() {
+        clear_lock_infos();
+
+        let lock = Mutex::new(Object);
+        start_capture();
+        contend(|| lock.lock().unwrap());
+        stop_capture();
+
+        let mut profile = Vec::new();
+        write_pprof(&mut profile).unwrap();
+        let profile = Profile::decode(&profile[..]).unwrap();
+
+        assert_eq!(profile.string_table[0], "");
+        assert!(profile.string_table.iter().any(|s| s == "contentions"));
+        assert!(profile.string_table.iter().any(|s| s == "delay"));
+        assert!(profile
+            .string_table
+            .iter()
+            .any(|s| s.starts_with("pprof::tests::profile")));
+        assert!(!profile
+            .string_table
+            .iter()
+            .any(|s| s.starts_with("locktick::")));
+
+        // Only the contended acquisition is included.
+        assert_eq!(profile.sample.len(), 1);
+        assert_eq!(profile.sample[0].value[0], 1);
+        assert!(profile.sample[0].value[1] > 0);
+        assert!(!profile.sample[0].location_id.is_empty());
+    }
+}
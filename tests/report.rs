@@ -0,0 +1,55 @@
+mod common;
+
+#[cfg(all(feature = "std", feature = "report", not(feature = "passthrough")))]
+mod tests {
+    use std::{env, mem, process::Command};
+
+    use locktick::{clear_lock_infos, report_on_exit, std::*};
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    // The environment variable used to only run the `reporting_process` test
+    // in a dedicated child process.
+    const CHILD_VAR: &str = "LOCKTICK_REPORT_CHILD";
+
+    #[test]
+    #[serial]
+    fn reporting_process() {
+        if env::var_os(CHILD_VAR).is_none() {
+            return;
+        }
+        clear_lock_infos();
+        report_on_exit();
+        report_on_exit();
+
+        let lock1 = Mutex::new(Object);
+        let lock2 = RwLock::new(Object);
+        drop(lock1.lock().unwrap());
+        mem::forget(lock2.write().unwrap());
+    }
+
+    #[test]
+    fn report() {
+        let output = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "tests::reporting_process", "--test-threads=1"])
+            .env(CHILD_VAR, "1")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        // The report is only printed once.
+        assert_eq!(
+            stderr
+                .matches("locktick summary (2 locks, 2 guards)")
+                .count(),
+            1
+        );
+        let (_, active) = stderr.split_once("still in use:\n").unwrap();
+        assert!(active.contains("(RwLock):"));
+        assert!(active.contains(" held for "));
+        assert!(!active.contains("(Mutex):"));
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+#[cfg(all(
+    unix,
+    feature = "std",
+    feature = "signal",
+    not(feature = "passthrough")
+))]
+mod tests {
+    use std::{fs, thread, time::Duration};
+
+    use locktick::{
+        clear_lock_infos, lock_snapshots,
+        signal::{install_dump_handler, DumpTarget, SIGUSR2},
+        std::*,
+    };
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    #[test]
+    #[serial]
+    fn dump() {
+        clear_lock_infos();
+        let path = std::env::temp_dir().join(format!("locktick-{}.dump", std::process::id()));
+        let _ = fs::remove_file(&path);
+        install_dump_handler(SIGUSR2, DumpTarget::File(path.clone())).unwrap();
+
+        let lock = RwLock::new(Object);
+        let read1 = lock.read().unwrap();
+        let read2 = lock.read().unwrap();
+
+        signal_hook::low_level::raise(SIGUSR2).unwrap();
+
+        let mut dump = String::new();
+        for _ in 0..100 {
+            dump = fs::read_to_string(&path).unwrap_or_default();
+            if dump.ends_with('\n') {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(dump.starts_with("locktick dump (signal"));
+        assert!(dump.contains("1 locks"));
+        assert_eq!(dump.lines().filter(|l| l.contains(" held for ")).count(), 2);
+
+        drop((read1, read2));
+        check_locks!(1, 2, 0);
+        fs::remove_file(&path).unwrap();
+    }
+}
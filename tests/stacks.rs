@@ -0,0 +1,40 @@
+mod common;
+
+#[cfg(all(feature = "std", feature = "stacks", not(feature = "passthrough")))]
+mod tests {
+    use locktick::{clear_lock_infos, stacks::*, std::*};
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    #[test]
+    #[serial]
+    fn folded() {
+        clear_lock_infos();
+
+        let lock = Mutex::new(Object);
+        start_capture();
+        contend(|| lock.lock().unwrap());
+        stop_capture();
+        contend(|| lock.lock().unwrap());
+
+        let mut waits = Vec::new();
+        write_folded(&mut waits, StackWeight::WaitTime).unwrap();
+        let waits = String::from_utf8(waits).unwrap();
+        let lines = waits.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+
+        let (stack, value) = lines[0].rsplit_once(' ').unwrap();
+        assert!(value.parse::<u64>().unwrap() > 0);
+        assert!(stack.contains("stacks::common::contend"));
+        assert!(!stack.contains("locktick::"));
+        assert!(stack.ends_with("{{closure}}"));
+
+        let mut holds = Vec::new();
+        write_folded(&mut holds, StackWeight::HoldTime).unwrap();
+        let holds = String::from_utf8(holds).unwrap();
+        // Only the guard that had to be waited for is included.
+        assert_eq!(holds.lines().count(), 1);
+    }
+}
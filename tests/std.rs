@@ -1,13 +1,27 @@
 mod common;
 
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", not(feature = "passthrough")))]
 mod tests {
-    use locktick::{clear_lock_infos, lock_snapshots, std::*};
+    use std::{thread, time::Duration};
+
+    use locktick::{
+        clear_lock_infos, condvar_snapshots, lock_snapshots, set_enabled, set_filters,
+        set_sampling, std::*, Filters, LockKind, LockOptions, Sampling,
+    };
     use serial_test::serial;
 
     use super::*;
     use common::*;
 
+    // A lock created within a trait implementation.
+    struct Wrapped(#[allow(dead_code)] Mutex<Object>);
+
+    impl Default for Wrapped {
+        fn default() -> Self {
+            Self(Mutex::new(Object))
+        }
+    }
+
     #[test]
     #[serial]
     fn mutex() {
@@ -57,4 +71,187 @@ mod tests {
         let _lock2 = RwLock::new(Object);
         check_locks!(2, 3, 0);
     }
+
+    #[test]
+    #[serial]
+    fn enable_disable() {
+        clear_lock_infos();
+
+        let lock = RwLock::new(Object);
+        let read1 = lock.read().unwrap();
+        check_guard!(read1, 1, 1);
+
+        set_enabled(false);
+        // Locks are registered even while the accounting is disabled.
+        let _lock2 = Mutex::new(Object);
+        check_locks!(2, 1, 1);
+
+        let read2 = lock.read().unwrap();
+        assert!(!read2.is_tracked());
+        check_locks!(2, 1, 1);
+        drop(read2);
+        // A guard acquired while enabled is still accounted for.
+        drop(read1);
+        check_locks!(2, 1, 0);
+        assert!(lock.try_write().is_ok());
+        check_locks!(2, 1, 0);
+
+        set_enabled(true);
+        let write = lock.write().unwrap();
+        check_guard!(write, 1, 1);
+        assert!(write.is_tracked());
+        check_locks!(2, 2, 1);
+    }
+
+    #[test]
+    #[serial]
+    fn sampling() {
+        clear_lock_infos();
+
+        let lock = Mutex::new(Object);
+        set_sampling(Sampling::OneIn(4));
+        let tracked = (0..8).filter(|_| lock.lock().unwrap().is_tracked()).count();
+        assert_eq!(tracked, 2);
+
+        let locks = lock_snapshots();
+        let guard = locks[0].known_guards.values().next().unwrap();
+        assert_eq!(guard.num_uses, 2);
+        assert_eq!(guard.estimated_uses, 8);
+        assert!(guard.is_sampled());
+        assert!(guard
+            .to_string()
+            .contains("calls: ~8 (estimated from 2 samples)"));
+
+        clear_lock_infos();
+
+        let lock = Mutex::new(Object);
+        set_sampling(Sampling::Interval(Duration::from_secs(3600)));
+        // Only the first acquisition within the interval is tracked.
+        let tracked = (0..8).filter(|_| lock.lock().unwrap().is_tracked()).count();
+        assert_eq!(tracked, 1);
+
+        set_sampling(Sampling::All);
+        let guard = lock.lock().unwrap();
+        check_guard!(guard, 1, 1);
+        assert!(guard.is_tracked());
+    }
+
+    #[test]
+    #[serial]
+    fn filters() {
+        clear_lock_infos();
+
+        set_filters(Filters {
+            exclude_kinds: vec![LockKind::RwLock],
+            ..Default::default()
+        });
+        let _mutex = Mutex::new(Object);
+        let rwlock = RwLock::new(Object);
+        assert!(!rwlock.read().unwrap().is_tracked());
+        check_locks!(1, 0, 0);
+
+        clear_lock_infos();
+
+        set_filters(Filters {
+            include_names: vec!["tracked".into()],
+            ..Default::default()
+        });
+        let named = Mutex::with_options(Object, LockOptions::named("tracked"));
+        let _unnamed = Mutex::new(Object);
+        assert!(named.lock().unwrap().is_tracked());
+        let locks = lock_snapshots();
+        assert_eq!(locks.len(), 1);
+        assert_eq!(locks[0].name.as_deref(), Some("tracked"));
+
+        clear_lock_infos();
+
+        // Only whole module names are matched.
+        set_filters(Filters {
+            include_modules: vec![module_path!()[..module_path!().len() - 1].into()],
+            ..Default::default()
+        });
+        let _lock = Mutex::new(Object);
+        check_locks!(0, 0, 0);
+
+        set_filters(Filters {
+            exclude_modules: vec![module_path!().into()],
+            ..Default::default()
+        });
+        let _lock = Mutex::new(Object);
+        check_locks!(0, 0, 0);
+
+        set_filters(Filters {
+            include_modules: vec![module_path!().into()],
+            ..Default::default()
+        });
+        let _lock = Mutex::new(Object);
+        check_locks!(1, 0, 0);
+
+        // The methods of trait implementations belong to the module of the type.
+        clear_lock_infos();
+        let _wrapped = Wrapped::default();
+        check_locks!(1, 0, 0);
+
+        set_filters(Filters {
+            exclude_modules: vec![module_path!().into()],
+            ..Default::default()
+        });
+        let _wrapped = Wrapped::default();
+        check_locks!(1, 0, 0);
+
+        set_filters(Default::default());
+    }
+
+    #[test]
+    #[serial]
+    fn condvar() {
+        clear_lock_infos();
+
+        let lock = Mutex::new(false);
+        let condvar = Condvar::new();
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                // The task waiting on the condvar is waiting to reacquire the guard.
+                let locks = lock_snapshots();
+                assert!(locks[0].known_guards.values().any(|g| g.num_waiting() == 1));
+                *lock.lock().unwrap() = true;
+                condvar.notify_one();
+            });
+
+            let guard = lock.lock().unwrap();
+            let guard = condvar.wait_while(guard, |ready| !*ready).unwrap();
+            assert!(*guard);
+            check_locks!(1, 2, 1);
+        });
+
+        let locks = lock_snapshots();
+        let waiter = locks[0]
+            .known_guards
+            .values()
+            .find(|guard| guard.num_uses >= 2)
+            .unwrap();
+        // The guard isn't considered held while waiting on the condvar.
+        assert!(waiter.max_duration < Duration::from_millis(50));
+
+        let (guard, result) = condvar
+            .wait_timeout(lock.lock().unwrap(), Duration::from_millis(10))
+            .unwrap();
+        assert!(result.timed_out());
+        assert!(guard.is_tracked());
+
+        let (guard, result) = condvar
+            .wait_timeout_while(guard, Duration::from_millis(10), |ready| *ready)
+            .unwrap();
+        assert!(result.timed_out());
+        assert!(guard.is_tracked());
+        drop(guard);
+
+        let condvars = condvar_snapshots();
+        assert_eq!(condvars.len(), 1);
+        assert_eq!(condvars[0].num_notify_one, 1);
+        assert!(condvars[0].num_wakeups >= 1);
+        assert_eq!(condvars[0].num_timeouts, 2);
+        assert_eq!(condvars[0].num_waiting(), 0);
+    }
 }
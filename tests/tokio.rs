@@ -1,8 +1,15 @@
 mod common;
 
-#[cfg(feature = "tokio")]
+#[cfg(all(feature = "tokio", not(feature = "passthrough")))]
 mod tests {
-    use locktick::{clear_lock_infos, lock_snapshots, tokio::*};
+    use std::{
+        future::{poll_fn, Future},
+        pin::pin,
+        sync::Arc,
+        task::Poll,
+    };
+
+    use locktick::{clear_lock_infos, lock_snapshots, tokio::*, LockKind, Notifications, Permits};
     use serial_test::serial;
 
     use super::*;
@@ -57,4 +64,156 @@ mod tests {
         let _lock2 = RwLock::new(Object);
         check_locks!(2, 3, 0);
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn owned() {
+        clear_lock_infos();
+
+        let mutex = Arc::new(Mutex::new(Object));
+        let guard = mutex.clone().lock_owned().await;
+        check_guard!(guard, 1, 1);
+        assert!(mutex.clone().try_lock_owned().is_err());
+        // The guard is held until the task that it was moved into ends.
+        tokio::spawn(async move { drop(guard) }).await.unwrap();
+        check_locks!(1, 1, 0);
+
+        let rwlock = Arc::new(RwLock::new(Object));
+        let write = rwlock.clone().write_owned().await;
+        check_guard!(write, 1, 1);
+        let reader = tokio::spawn(rwlock.clone().read_owned());
+        tokio::task::yield_now().await;
+        // The task is queued until the write guard is released.
+        let locks = lock_snapshots();
+        let lock = locks.iter().find(|l| l.kind == LockKind::RwLock).unwrap();
+        assert_eq!(lock.num_waiting(), 1);
+        drop(write);
+        drop(reader.await.unwrap());
+        let read = rwlock.clone().try_read_owned().unwrap();
+        check_guard!(read, 1, 1);
+        assert!(rwlock.clone().try_write_owned().is_err());
+        // The owned guards keep the lock alive, like in tokio.
+        let rwlock = Arc::try_unwrap(rwlock).err().unwrap();
+        drop(read);
+        check_locks!(2, 4, 0);
+        let _object: Object = Arc::try_unwrap(rwlock).ok().unwrap().into_inner();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn semaphore() {
+        clear_lock_infos();
+
+        let semaphore = Arc::new(Semaphore::new(3));
+        check_locks!(1, 0, 0);
+        assert_eq!(lock_snapshots()[0].kind, LockKind::Semaphore);
+
+        let permits1 = semaphore.acquire_many(2).await.unwrap();
+        check_guard!(permits1, 1, 1);
+        assert!(semaphore.try_acquire_many(2).is_err());
+        let permit2 = semaphore.try_acquire().unwrap();
+        check_guard!(permit2, 1, 1);
+        assert_eq!(
+            lock_snapshots()[0].permits,
+            Some(Permits {
+                held: 3,
+                max_held: 3,
+                available: 0,
+            })
+        );
+
+        let waiter = tokio::spawn(semaphore.clone().acquire_owned());
+        tokio::task::yield_now().await;
+        // The task is queued until some permits are released.
+        assert_eq!(lock_snapshots()[0].num_waiting(), 1);
+        drop(permits1);
+        drop(waiter.await.unwrap().unwrap());
+        check_locks!(1, 3, 1);
+
+        assert_eq!(
+            lock_snapshots()[0].permits,
+            Some(Permits {
+                held: 1,
+                max_held: 3,
+                available: 2,
+            })
+        );
+
+        // A forgotten permit is no longer held, nor available.
+        permit2.into_inner().forget();
+        let permit3 = semaphore.try_acquire().unwrap();
+        drop(permit3);
+        assert_eq!(
+            lock_snapshots()[0].permits,
+            Some(Permits {
+                held: 0,
+                max_held: 3,
+                available: 2,
+            })
+        );
+        assert_eq!(semaphore.available_permits(), 2);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn notify() {
+        clear_lock_infos();
+
+        let notify = Arc::new(Notify::new());
+        check_locks!(1, 0, 0);
+
+        let waiter = notify.clone();
+        let waiter = tokio::spawn(async move { waiter.notified().await });
+        tokio::task::yield_now().await;
+        assert_eq!(lock_snapshots()[0].num_waiting(), 1);
+        notify.notify_one();
+        waiter.await.unwrap();
+        check_locks!(1, 1, 0);
+
+        // The notifications sent before the first poll are received.
+        let notified = notify.notified();
+        notify.notify_waiters();
+        notified.await;
+        check_locks!(1, 2, 0);
+
+        {
+            let mut notified = pin!(notify.notified());
+            poll_fn(|cx| {
+                assert!(notified.as_mut().poll(cx).is_pending());
+                Poll::Ready(())
+            })
+            .await;
+            assert_eq!(lock_snapshots()[0].num_waiting(), 1);
+        }
+        // Dropping the future cancels the wait.
+        assert_eq!(lock_snapshots()[0].num_waiting(), 0);
+
+        // An enabled future receives the notifications sent before it's polled.
+        let mut notified = pin!(notify.notified());
+        notified.as_mut().enable();
+        notify.notify_one();
+        notified.await;
+        check_locks!(1, 4, 0);
+
+        let locks = lock_snapshots();
+        assert_eq!(locks[0].kind, LockKind::Notify);
+        assert_eq!(
+            locks[0].notifications,
+            Some(Notifications {
+                notify_one: 2,
+                notify_waiters: 1,
+            })
+        );
+        let num_uses = locks[0]
+            .known_guards
+            .values()
+            .map(|guard| guard.num_uses)
+            .sum::<usize>();
+        assert_eq!(num_uses, 3);
+        // Waiting for notifications doesn't involve holding anything.
+        assert!(locks[0]
+            .known_guards
+            .values()
+            .all(|guard| guard.total_duration.is_zero()));
+    }
 }
@@ -0,0 +1,234 @@
+mod common;
+
+#[cfg(all(feature = "std", feature = "tracing", not(feature = "passthrough")))]
+mod tests {
+    use std::{
+        collections::HashMap,
+        fmt,
+        sync::{Arc, Mutex as StdMutex},
+        thread,
+        time::Duration,
+    };
+
+    use locktick::{
+        clear_lock_infos,
+        std::*,
+        tracing::{set_thresholds, Thresholds},
+    };
+    use serial_test::serial;
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        subscriber::with_default,
+        Event, Level, Metadata, Subscriber,
+    };
+
+    use super::*;
+    use common::*;
+
+    #[derive(Debug)]
+    struct RecordedSpan {
+        name: &'static str,
+        fields: HashMap<&'static str, String>,
+        closed: bool,
+    }
+
+    #[derive(Debug)]
+    struct RecordedEvent {
+        level: Level,
+        fields: HashMap<&'static str, String>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<&'static str, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name(), format!("{value:?}"));
+        }
+    }
+
+    // Records all the spans and events, along with their fields.
+    #[derive(Clone, Default)]
+    struct SpanRecorder {
+        spans: Arc<StdMutex<Vec<RecordedSpan>>>,
+        events: Arc<StdMutex<Vec<RecordedEvent>>>,
+    }
+
+    impl Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut spans = self.spans.lock().unwrap();
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            spans.push(RecordedSpan {
+                name: attrs.metadata().name(),
+                fields,
+                closed: false,
+            });
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            let span = &mut spans[span.into_u64() as usize - 1];
+            values.record(&mut FieldVisitor(&mut span.fields));
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = HashMap::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.events.lock().unwrap().push(RecordedEvent {
+                level: *event.metadata().level(),
+                fields,
+            });
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+
+        fn try_close(&self, span: Id) -> bool {
+            self.spans.lock().unwrap()[span.into_u64() as usize - 1].closed = true;
+            true
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn spans() {
+        clear_lock_infos();
+
+        let recorder = SpanRecorder::default();
+        let lock = Mutex::new(Object);
+
+        contend(|| with_default(recorder.clone(), || lock.lock().unwrap()));
+
+        let spans = recorder.spans.lock().unwrap();
+        assert!(spans.iter().all(|span| span.closed));
+
+        let holds = spans
+            .iter()
+            .filter(|span| span.name == "lock.hold")
+            .collect::<Vec<_>>();
+        assert_eq!(holds.len(), 2);
+        assert!(holds.iter().all(|span| span.fields["guard.kind"] == "Lock"
+            && span.fields.contains_key("lock.location")
+            && span.fields.contains_key("duration_ns")));
+
+        let waits = spans
+            .iter()
+            .filter(|span| span.name == "lock.wait")
+            .collect::<Vec<_>>();
+        assert_eq!(waits.len(), 1);
+        assert!(waits[0].fields["wait_time_ns"].parse::<u64>().unwrap() > 0);
+        assert!(!waits[0].fields.contains_key("cancelled"));
+        assert_eq!(
+            waits[0].fields["guard.index"],
+            holds[1].fields["guard.index"]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn thresholds() {
+        clear_lock_infos();
+
+        set_thresholds(Some(Thresholds {
+            hold: Some(Duration::from_millis(10)),
+            level: Level::INFO,
+            min_interval: Duration::from_millis(500),
+            ..Default::default()
+        }));
+
+        let recorder = SpanRecorder::default();
+        let lock = Mutex::new(Object);
+
+        with_default(recorder.clone(), || {
+            let hold = || {
+                let _guard = lock.lock().unwrap();
+                thread::sleep(Duration::from_millis(20));
+            };
+
+            // A short hold doesn't exceed the threshold.
+            drop(lock.lock().unwrap());
+            // Only the first of these is reported, as the others are rate-limited.
+            for _ in 0..3 {
+                hold();
+            }
+            thread::sleep(Duration::from_millis(500));
+            // This one is reported along with the number of suppressed events.
+            hold();
+        });
+
+        set_thresholds(None);
+
+        let events = recorder.events.lock().unwrap();
+        let reported = events
+            .iter()
+            .filter(|event| event.fields.contains_key("threshold_ns"))
+            .collect::<Vec<_>>();
+        assert_eq!(reported.len(), 2);
+        assert!(reported.iter().all(|event| event.level == Level::INFO
+            && event.fields["guard.kind"] == "Lock"
+            && event.fields["duration_ns"].parse::<u64>().unwrap() >= 20_000_000
+            && event.fields["threshold_ns"] == "10000000"));
+        assert_eq!(reported[0].fields["suppressed"], "0");
+        assert_eq!(reported[1].fields["suppressed"], "2");
+    }
+}
+
+#[cfg(all(
+    feature = "std",
+    feature = "tracing_spans",
+    not(feature = "passthrough")
+))]
+mod span_tests {
+    use std::{thread, time::Duration};
+
+    use locktick::{clear_lock_infos, lock_snapshots, std::*, tracing::SpanLayer};
+    use serial_test::serial;
+    use tracing::{info_span, subscriber::with_default};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use common::*;
+
+    #[test]
+    #[serial]
+    fn span_stats() {
+        clear_lock_infos();
+
+        let subscriber = tracing_subscriber::registry().with(SpanLayer::new(&["endpoint"]));
+        let lock = Mutex::new(Object);
+        let use_lock = || {
+            let _guard = lock.lock().unwrap();
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        with_default(subscriber, || {
+            for endpoint in ["/users", "/users", "/posts"] {
+                let _span = info_span!("request", endpoint, id = 42).entered();
+                use_lock();
+            }
+            // Uses outside of any span aren't attributed to one.
+            use_lock();
+        });
+
+        let locks = lock_snapshots();
+        let guard = locks[0].known_guards.values().next().unwrap();
+        assert_eq!(guard.num_uses, 4);
+        assert_eq!(guard.span_stats.len(), 2);
+
+        let users = &guard.span_stats["request{endpoint=\"/users\"}"];
+        assert_eq!(users.num_uses, 2);
+        assert!(users.total_duration >= Duration::from_millis(20));
+        let posts = &guard.span_stats["request{endpoint=\"/posts\"}"];
+        assert_eq!(posts.num_uses, 1);
+        assert!(posts.max_duration >= Duration::from_millis(10));
+    }
+}
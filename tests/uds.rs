@@ -0,0 +1,87 @@
+mod common;
+
+#[cfg(all(unix, feature = "std", feature = "uds", not(feature = "passthrough")))]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixStream,
+    };
+
+    use locktick::{
+        clear_lock_infos, is_enabled, lock_snapshots, sampling, std::*, uds::start_server, Sampling,
+    };
+    use serial_test::serial;
+
+    use super::*;
+    use common::*;
+
+    fn command(stream: &mut BufReader<UnixStream>, command: &str) -> Vec<String> {
+        writeln!(stream.get_mut(), "{command}").unwrap();
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            stream.read_line(&mut line).unwrap();
+            let line = line.trim_end().to_owned();
+            let last = line == "OK" || line.starts_with("ERR");
+            lines.push(line);
+            if last {
+                return lines;
+            }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn commands() {
+        clear_lock_infos();
+        let path = std::env::temp_dir().join(format!("locktick-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        start_server(&path).unwrap();
+        // An idle connection doesn't prevent the others from being served.
+        let _idle = UnixStream::connect(&path).unwrap();
+        let mut stream = BufReader::new(UnixStream::connect(&path).unwrap());
+
+        let lock1 = Mutex::new(Object);
+        let lock2 = Mutex::new(Object);
+        let guard = lock1.lock().unwrap();
+        drop(lock2.lock().unwrap());
+
+        let dump = command(&mut stream, "dump");
+        assert_eq!(dump.len(), 5);
+        assert_eq!(dump.last().unwrap(), "OK");
+
+        let active = command(&mut stream, "active");
+        assert_eq!(active.len(), 3);
+
+        let deadlocks = command(&mut stream, "deadlocks");
+        assert_eq!(deadlocks, ["OK"]);
+
+        assert_eq!(command(&mut stream, "reset"), ["OK"]);
+        let locks = lock_snapshots();
+        assert!(locks
+            .iter()
+            .flat_map(|l| l.known_guards.values())
+            .all(|g| g.num_uses == 0));
+
+        assert_eq!(command(&mut stream, "disable"), ["OK"]);
+        assert!(!is_enabled());
+        assert_eq!(command(&mut stream, "enable"), ["OK"]);
+        assert!(is_enabled());
+
+        assert_eq!(command(&mut stream, "sample 4"), ["OK"]);
+        assert_eq!(sampling(), Sampling::OneIn(4));
+        assert_eq!(command(&mut stream, "sample 1"), ["OK"]);
+        assert_eq!(sampling(), Sampling::All);
+        let invalid = command(&mut stream, "sample 0");
+        assert_eq!(invalid, ["ERR invalid sampling rate: 0"]);
+
+        let unknown = command(&mut stream, "frobnicate");
+        assert_eq!(unknown, ["ERR unknown command: frobnicate"]);
+        let invalid = command(&mut stream, "dump all");
+        assert_eq!(invalid, ["ERR dump takes no arguments"]);
+
+        drop(guard);
+        check_locks!(2, 2, 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+}